@@ -0,0 +1,25 @@
+use computer::{Computer, IOMode};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+// Sums 1..=100000 in a tight loop.
+const SUM_LOOP: &str = "1101,0,100000,100,1,101,100,101,1001,100,-1,100,1005,100,4,4,101,99";
+
+fn bench_program(c: &mut Criterion, name: &str, program: &str, decode_cache: bool) {
+    let mut computer = Computer::from_string(program, IOMode::Buffer).unwrap();
+    computer.decode_cache = decode_cache;
+    c.bench_function(name, |b| {
+        b.iter(|| {
+            let mut c = computer.clone();
+            c.run().unwrap();
+            c.output_from_buffer()
+        })
+    });
+}
+
+fn sum_loop(c: &mut Criterion) {
+    bench_program(c, "sum_loop", SUM_LOOP, true);
+    bench_program(c, "sum_loop_uncached", SUM_LOOP, false);
+}
+
+criterion_group!(benches, sum_loop);
+criterion_main!(benches);
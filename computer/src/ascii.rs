@@ -0,0 +1,139 @@
+use super::{Buffer, Computer, IntcodeIo, IoError, Word};
+use std::io::{stdin, stdout, Write};
+
+const NEWLINE: i64 = '\n' as i64;
+
+fn to_char<W: Word>(value: &W) -> Option<char> {
+    match value.to_i64() {
+        Some(c @ 0..=127) => Some(c as u8 as char),
+        _ => None,
+    }
+}
+
+fn encode<W: Word>(line: &str) -> impl Iterator<Item = W> + '_ {
+    line.bytes()
+        .map(|b| W::from_i64(b as i64))
+        .chain(std::iter::once(W::from_i64(NEWLINE)))
+}
+
+// Output of a program that speaks ASCII. A final value that isn't a character,
+// usually the answer, is kept apart in `value`. Any others are written into the
+// text as numbers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsciiOutput<W = i64> {
+    pub text: String,
+    pub value: Option<W>,
+}
+
+impl<W: Word> AsciiOutput<W> {
+    pub fn from_values<I: IntoIterator<Item = W>>(values: I) -> AsciiOutput<W> {
+        let mut values: Vec<W> = values.into_iter().collect();
+        let value = match values.last().map(to_char) {
+            Some(None) => values.pop(),
+            _ => None,
+        };
+
+        let mut text = String::new();
+        for v in values {
+            match to_char(&v) {
+                Some(c) => text.push(c),
+                None => text.push_str(&v.to_string()),
+            }
+        }
+        AsciiOutput { text, value }
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.text.lines()
+    }
+}
+
+impl<W: Word> Computer<W> {
+    // Queues `line` and a newline as character codes on the input buffer
+    pub fn send_line(&mut self, line: &str) {
+        self.buffer_io().input.extend(encode(line));
+    }
+
+    // False, queueing nothing, when the computer isn't using buffer io
+    pub fn try_send_line(&mut self, line: &str) -> bool {
+        match self.try_buffer_io() {
+            Some(buffer) => {
+                buffer.input.extend(encode(line));
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Takes everything in the output buffer
+    pub fn read_ascii(&mut self) -> AsciiOutput<W> {
+        self.try_read_ascii()
+            .expect("Computer is not using buffer io")
+    }
+
+    pub fn try_read_ascii(&mut self) -> Option<AsciiOutput<W>> {
+        let output = &mut self.try_buffer_io()?.output;
+        Some(AsciiOutput::from_values(output.drain(..)))
+    }
+
+    // Takes lines of text from the output buffer, stopping at the first value that
+    // isn't a character so it can still be read with `output_from_buffer`
+    pub fn read_lines(&mut self) -> Vec<String> {
+        self.try_read_lines()
+            .expect("Computer is not using buffer io")
+    }
+
+    pub fn try_read_lines(&mut self) -> Option<Vec<String>> {
+        let output = &mut self.try_buffer_io()?.output;
+        let text = output.iter().take_while(|v| to_char(*v).is_some()).count();
+        let text: String = output.drain(..text).filter_map(|v| to_char(&v)).collect();
+        Some(text.lines().map(str::to_string).collect())
+    }
+}
+
+// Like `StdioIo` for programs that speak ASCII: their text is echoed as it is
+// written, and each line typed in is sent as character codes ending in a newline.
+// Values that aren't characters are printed on a line of their own.
+#[derive(Debug, Clone)]
+pub struct AsciiStdioIo<W = i64> {
+    pending: Buffer<W>,
+}
+
+impl<W> AsciiStdioIo<W> {
+    pub fn new() -> AsciiStdioIo<W> {
+        AsciiStdioIo {
+            pending: Buffer::new(),
+        }
+    }
+}
+
+impl<W> Default for AsciiStdioIo<W> {
+    fn default() -> AsciiStdioIo<W> {
+        AsciiStdioIo::new()
+    }
+}
+
+impl<W: Word> IntcodeIo<W> for AsciiStdioIo<W> {
+    fn read(&mut self) -> Result<Option<W>, IoError> {
+        if self.pending.is_empty() {
+            let _ = stdout().flush();
+            let mut s = String::new();
+            match stdin().read_line(&mut s) {
+                Ok(0) => return Ok(None),
+                Ok(_) => {}
+                Err(e) => return Err(IoError::InvalidInput(e.to_string())),
+            }
+            self.pending
+                .extend(encode(s.trim_end_matches(&['\r', '\n'][..])));
+        }
+        Ok(self.pending.pop_front())
+    }
+
+    fn write(&mut self, value: W) -> Result<(), IoError> {
+        match to_char(&value) {
+            Some(c) => print!("{}", c),
+            None => println!("{}", value),
+        }
+        Ok(())
+    }
+}
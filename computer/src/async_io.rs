@@ -0,0 +1,144 @@
+use super::{Buffer, Computer, ComputerError, InputPolicy, IntcodeIo, IoError, StopReason, Word};
+use futures::{FutureExt, Sink, SinkExt, Stream, StreamExt};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+// Instructions run between yields back to the executor, so a busy computer
+// can't starve the others sharing its thread.
+pub const ASYNC_SLICE: u64 = 4096;
+
+// Stands in for the computer's io while it runs async. Once the sink is closed
+// the next output fails like a disconnected channel.
+#[derive(Debug, Clone)]
+struct AsyncIo<W> {
+    input: Buffer<W>,
+    output: Buffer<W>,
+    closed: bool,
+}
+
+impl<W: Word> IntcodeIo<W> for AsyncIo<W> {
+    fn read(&mut self) -> Result<Option<W>, IoError> {
+        Ok(self.input.pop_front())
+    }
+
+    fn write(&mut self, value: W) -> Result<(), IoError> {
+        match self.closed {
+            true => Err(IoError::Disconnected),
+            false => {
+                self.output.push_back(value);
+                Ok(())
+            }
+        }
+    }
+}
+
+// Puts the computer's own io back even if the future running it is dropped early
+struct RestoreIo<'a, W: Word> {
+    computer: &'a mut Computer<W>,
+    io: Option<Box<dyn IntcodeIo<W>>>,
+}
+
+impl<W: Word> Drop for RestoreIo<'_, W> {
+    fn drop(&mut self) {
+        if let Some(io) = self.io.take() {
+            self.computer.io = io;
+        }
+    }
+}
+
+impl<W: Word> Computer<W> {
+    // Runs with inputs taken from `input` and outputs sent to `output`, waiting on either
+    // without blocking the thread. Stops with `NeedsInput` once `input` ends, unless
+    // `InputPolicy::Default` supplies a value whenever `input` has none ready.
+    // The computer's own io is put back afterwards, also when the future is dropped,
+    // and the time limit covers the whole run.
+    pub async fn run_async<I, O>(
+        &mut self,
+        mut input: I,
+        mut output: O,
+    ) -> Result<StopReason<W>, ComputerError<W>>
+    where
+        I: Stream<Item = W> + Unpin,
+        O: Sink<W> + Unpin,
+    {
+        let io = std::mem::replace(
+            &mut self.io,
+            Box::new(AsyncIo {
+                input: Buffer::new(),
+                output: Buffer::new(),
+                closed: false,
+            }),
+        );
+        let guard = RestoreIo {
+            computer: self,
+            io: Some(io),
+        };
+        guard.computer.drive(&mut input, &mut output).await
+    }
+
+    fn async_io(&mut self) -> &mut AsyncIo<W> {
+        self.io_as::<AsyncIo<W>>().expect("async io missing")
+    }
+
+    async fn drive<I, O>(
+        &mut self,
+        input: &mut I,
+        output: &mut O,
+    ) -> Result<StopReason<W>, ComputerError<W>>
+    where
+        I: Stream<Item = W> + Unpin,
+        O: Sink<W> + Unpin,
+    {
+        let deadline = self.deadline();
+        let mut input = input.fuse();
+        loop {
+            // A default stands in for input only once the stream has nothing ready
+            if let InputPolicy::Default(_) = self.input_policy {
+                while self.async_io().input.len() < ASYNC_SLICE as usize {
+                    match input.next().now_or_never() {
+                        Some(Some(value)) => self.async_io().input.push_back(value),
+                        _ => break,
+                    }
+                }
+            }
+            let mut budget = ASYNC_SLICE;
+            let reason = self.run_before(deadline, |_| {
+                budget -= 1;
+                budget == 0
+            });
+
+            let outputs: Vec<W> = self.async_io().output.drain(..).collect();
+            for value in outputs {
+                if output.send(value).await.is_err() {
+                    self.async_io().closed = true;
+                    break;
+                }
+            }
+
+            match reason? {
+                StopReason::NeedsInput => match input.next().await {
+                    Some(value) => self.async_io().input.push_back(value),
+                    None => return Ok(StopReason::NeedsInput),
+                },
+                StopReason::Breakpoint(_) if budget == 0 => YieldNow(false).await,
+                reason => return Ok(reason),
+            }
+        }
+    }
+}
+
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
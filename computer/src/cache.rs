@@ -0,0 +1,110 @@
+use super::paged::PAGE_SIZE;
+use super::{Operation, Word, MAX_CUSTOM_PARAMETERS};
+use std::collections::BTreeMap;
+
+// Built-in opcodes take at most as many parameters as custom ones
+pub const MAX_INSTRUCTION_LENGTH: usize = MAX_CUSTOM_PARAMETERS + 1;
+
+// Pages below this are found by index, the rest through a map
+const NEAR_PAGES: usize = 1024;
+
+type Page<W> = Box<[Option<Box<Operation<W>>>]>;
+
+// Decoded instructions keyed by the address of their opcode. Any write into the
+// words an instruction was decoded from drops it, so self-modifying code is re-decoded.
+// Paged like `PagedMemory`, so only pages holding executed code take up space.
+#[derive(Debug, Clone)]
+pub struct DecodeCache<W> {
+    near: Vec<Option<Page<W>>>,
+    far: BTreeMap<usize, Page<W>>,
+    // The address and length of the instruction taken out to run, and whether
+    // a write has gone into it since
+    running: Option<(usize, usize)>,
+    stale: bool,
+}
+
+impl<W: Word> DecodeCache<W> {
+    pub fn new() -> DecodeCache<W> {
+        DecodeCache {
+            near: Vec::new(),
+            far: BTreeMap::new(),
+            running: None,
+            stale: false,
+        }
+    }
+
+    // Takes the instruction at `ip` out while it runs, `put_back` returns it
+    pub fn take(&mut self, ip: usize) -> Option<Box<Operation<W>>> {
+        let op = self.page_mut(ip / PAGE_SIZE)?[ip % PAGE_SIZE].take()?;
+        self.running = Some((ip, op.data.len()));
+        self.stale = false;
+        Some(op)
+    }
+
+    // Also takes newly decoded instructions, unless they wrote over themselves
+    pub fn put_back(&mut self, ip: usize, op: Box<Operation<W>>) {
+        let stale = self.stale && self.running == Some((ip, op.data.len()));
+        self.running = None;
+        if stale {
+            return;
+        }
+        match self.page_mut(ip / PAGE_SIZE) {
+            Some(page) => page[ip % PAGE_SIZE] = Some(op),
+            None => self.insert_page(ip / PAGE_SIZE)[ip % PAGE_SIZE] = Some(op),
+        }
+    }
+
+    // Marks the running instruction so a new decode isn't put back over a write into it
+    pub fn start(&mut self, ip: usize, length: usize) {
+        self.running = Some((ip, length));
+        self.stale = false;
+    }
+
+    pub fn invalidate(&mut self, address: usize) {
+        if let Some((ip, length)) = self.running {
+            if ip <= address && address < ip + length {
+                self.stale = true;
+            }
+        }
+        let start = address.saturating_sub(MAX_INSTRUCTION_LENGTH - 1);
+        for ip in start..=address {
+            if let Some(page) = self.page_mut(ip / PAGE_SIZE) {
+                let slot = &mut page[ip % PAGE_SIZE];
+                if let Some(op) = slot {
+                    if ip + op.data.len() > address {
+                        *slot = None;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.near.clear();
+        self.far.clear();
+        self.stale = true;
+    }
+
+    fn insert_page(&mut self, index: usize) -> &mut Page<W> {
+        match index < NEAR_PAGES {
+            true => {
+                if self.near.len() <= index {
+                    self.near.resize_with(index + 1, || None);
+                }
+                self.near[index].get_or_insert_with(new_page)
+            }
+            false => self.far.entry(index).or_insert_with(new_page),
+        }
+    }
+
+    fn page_mut(&mut self, index: usize) -> Option<&mut Page<W>> {
+        match index < NEAR_PAGES {
+            true => self.near.get_mut(index)?.as_mut(),
+            false => self.far.get_mut(&index),
+        }
+    }
+}
+
+fn new_page<W>() -> Page<W> {
+    (0..PAGE_SIZE).map(|_| None).collect()
+}
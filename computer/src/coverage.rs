@@ -0,0 +1,183 @@
+use super::{Computer, PagedMemory, Word};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::fs::{read_to_string, write};
+use std::io;
+use std::path::Path;
+
+// How an address was used. An address that was both executed and used as data,
+// as in self-modifying code, counts as executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    Executed,
+    Data,
+    Untouched,
+}
+
+// The addresses a computer executed or used as data while set as its `coverage`.
+// Coverage from several runs of the same program can be combined with `merge`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Coverage {
+    // Start address of each executed instruction and its length
+    instructions: BTreeMap<usize, usize>,
+    reads: BTreeSet<usize>,
+    writes: BTreeSet<usize>,
+}
+
+impl Coverage {
+    pub fn new() -> Coverage {
+        Coverage::default()
+    }
+
+    pub fn merge(&mut self, other: &Coverage) {
+        for (&address, &length) in &other.instructions {
+            let entry = self.instructions.entry(address).or_insert(length);
+            *entry = (*entry).max(length);
+        }
+        self.reads.extend(&other.reads);
+        self.writes.extend(&other.writes);
+    }
+
+    pub fn instructions(&self) -> impl Iterator<Item = usize> + '_ {
+        self.instructions.keys().copied()
+    }
+
+    pub fn is_read(&self, address: usize) -> bool {
+        self.reads.contains(&address)
+    }
+
+    pub fn is_written(&self, address: usize) -> bool {
+        self.writes.contains(&address)
+    }
+
+    pub fn is_executed(&self, address: usize) -> bool {
+        self.instruction_at(address).is_some()
+    }
+
+    pub fn mark(&self, address: usize) -> Mark {
+        match self.is_executed(address) {
+            true => Mark::Executed,
+            false if self.is_read(address) || self.is_written(address) => Mark::Data,
+            false => Mark::Untouched,
+        }
+    }
+
+    // Counts of executed, data-only and untouched addresses below `len`
+    pub fn summary(&self, len: usize) -> (usize, usize, usize) {
+        let mut executed = 0;
+        let mut covered = 0;
+        for (&start, &length) in &self.instructions {
+            let (from, to) = (start.max(covered), (start + length).min(len));
+            executed += to.saturating_sub(from);
+            covered = covered.max(start + length);
+        }
+        let data = self
+            .reads
+            .union(&self.writes)
+            .filter(|&&address| address < len && !self.is_executed(address))
+            .count();
+        (executed, data, len - executed - data)
+    }
+
+    // Lists `memory` one instruction or word per line, marked `X` when executed,
+    // `D` when only used as data and `.` when untouched. Addresses past the end of
+    // `memory` are listed too if they were used, with the untouched ones between
+    // them collapsed into a line each.
+    pub fn listing<W: Word>(&self, memory: &PagedMemory<W>) -> String {
+        let used = [
+            self.instructions.iter().next_back().map(|(a, l)| a + l),
+            self.reads.iter().next_back().map(|a| a + 1),
+            self.writes.iter().next_back().map(|a| a + 1),
+        ];
+        let len = used
+            .iter()
+            .flatten()
+            .fold(memory.len(), |len, &end| len.max(end));
+
+        let (executed, data, untouched) = self.summary(len);
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{} executed, {} data, {} untouched of {} words",
+            executed, data, untouched, len
+        );
+
+        let mut address = 0;
+        while address < len {
+            if address >= memory.len() && self.mark(address) == Mark::Untouched {
+                let gap = self.next_used(address).min(len) - address;
+                if gap > 1 {
+                    let _ = writeln!(out, "{:>6} . 0 ({} words)", address, gap);
+                    address += gap;
+                    continue;
+                }
+            }
+            let (mark, words) = match self.instructions.get(&address) {
+                Some(&length) => ('X', length.min(len - address)),
+                None => match self.mark(address) {
+                    Mark::Data => ('D', 1),
+                    _ => ('.', 1),
+                },
+            };
+            let values: Vec<String> = memory
+                .read_range(address..address + words)
+                .iter()
+                .map(|v| v.to_string())
+                .collect();
+            let _ = writeln!(out, "{:>6} {} {}", address, mark, values.join(","));
+            address += words;
+        }
+        out
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let json = serde_json::to_string(self).map_err(io::Error::other)?;
+        write(path, json)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Coverage> {
+        let json = read_to_string(path)?;
+        serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub(crate) fn execute(&mut self, address: usize, length: usize) {
+        self.instructions.insert(address, length);
+    }
+
+    pub(crate) fn read(&mut self, address: usize) {
+        self.reads.insert(address);
+    }
+
+    pub(crate) fn write(&mut self, address: usize) {
+        self.writes.insert(address);
+    }
+
+    // The first address from `address` on that was executed or used as data
+    fn next_used(&self, address: usize) -> usize {
+        [
+            self.instructions.range(address..).next().map(|(a, _)| *a),
+            self.reads.range(address..).next().copied(),
+            self.writes.range(address..).next().copied(),
+        ]
+        .iter()
+        .flatten()
+        .fold(usize::MAX, |next, &a| next.min(a))
+    }
+
+    fn instruction_at(&self, address: usize) -> Option<usize> {
+        self.instructions
+            .range(..=address)
+            .next_back()
+            .filter(|(start, length)| address < *start + *length)
+            .map(|(start, _)| *start)
+    }
+}
+
+impl<W: Word> Computer<W> {
+    // The listing of the program as loaded, None unless `coverage` is set
+    pub fn coverage_listing(&self) -> Option<String> {
+        let coverage = self.coverage.as_ref()?;
+        Some(coverage.listing(&self.mem.image))
+    }
+}
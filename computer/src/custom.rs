@@ -0,0 +1,180 @@
+use super::{Computer, ComputerError, OpCode, Operation, StopReason, Word};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+pub const MAX_CUSTOM_PARAMETERS: usize = 3;
+
+type Handler<W> = Arc<
+    dyn Fn(&mut Instruction<W>) -> Result<Option<StopReason<W>>, ComputerError<W>> + Send + Sync,
+>;
+
+#[derive(Clone)]
+struct CustomOpCode<W> {
+    parameters: usize,
+    handler: Handler<W>,
+}
+
+// Extra opcodes registered by the host through `Computer::register_opcode`. Built-in
+// opcodes always take priority, so registering one of their codes has no effect.
+#[derive(Clone)]
+pub struct OpCodeRegistry<W = i64> {
+    opcodes: HashMap<i64, CustomOpCode<W>>,
+}
+
+impl<W: Word> OpCodeRegistry<W> {
+    pub fn new() -> OpCodeRegistry<W> {
+        OpCodeRegistry {
+            opcodes: HashMap::new(),
+        }
+    }
+
+    fn register<F>(&mut self, code: i64, parameters: usize, handler: F)
+    where
+        F: Fn(&mut Instruction<W>) -> Result<Option<StopReason<W>>, ComputerError<W>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        assert!(
+            (0..100).contains(&code),
+            "custom opcode {} is not two digits",
+            code
+        );
+        assert!(
+            parameters <= MAX_CUSTOM_PARAMETERS,
+            "custom opcode {} takes more than {} parameters",
+            code,
+            MAX_CUSTOM_PARAMETERS
+        );
+        self.opcodes.insert(
+            code,
+            CustomOpCode {
+                parameters,
+                handler: Arc::new(handler),
+            },
+        );
+    }
+
+    fn unregister(&mut self, code: i64) -> bool {
+        self.opcodes.remove(&code).is_some()
+    }
+
+    pub fn opcode(&self, code: i64) -> Option<OpCode> {
+        self.opcodes.get(&code).map(|custom| OpCode::Custom {
+            code,
+            parameters: custom.parameters,
+        })
+    }
+
+    pub(crate) fn handler(&self, code: i64) -> Option<Handler<W>> {
+        self.opcodes.get(&code).map(|custom| custom.handler.clone())
+    }
+}
+
+impl<W: Word> Default for OpCodeRegistry<W> {
+    fn default() -> OpCodeRegistry<W> {
+        OpCodeRegistry::new()
+    }
+}
+
+impl<W> fmt::Debug for OpCodeRegistry<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut codes: Vec<_> = self.opcodes.keys().collect();
+        codes.sort();
+        f.debug_set().entries(codes).finish()
+    }
+}
+
+impl<W: Word> Computer<W> {
+    // Code already decoded is decoded again, in case its length changed
+    pub fn register_opcode<F>(&mut self, code: i64, parameters: usize, handler: F)
+    where
+        F: Fn(&mut Instruction<W>) -> Result<Option<StopReason<W>>, ComputerError<W>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.opcodes.register(code, parameters, handler);
+        self.mem.decoded.clear();
+    }
+
+    pub fn unregister_opcode(&mut self, code: i64) -> bool {
+        self.mem.decoded.clear();
+        self.opcodes.unregister(code)
+    }
+
+    pub fn opcodes(&self) -> &OpCodeRegistry<W> {
+        &self.opcodes
+    }
+}
+
+// What a custom opcode handler sees of the computer while it runs.
+// Parameters are resolved with the instruction's modes like built-in opcodes.
+pub struct Instruction<'a, W = i64> {
+    computer: &'a mut Computer<W>,
+    op: &'a Operation<W>,
+    jumped: bool,
+}
+
+impl<'a, W: Word> Instruction<'a, W> {
+    pub(crate) fn new(computer: &'a mut Computer<W>, op: &'a Operation<W>) -> Instruction<'a, W> {
+        Instruction {
+            computer,
+            op,
+            jumped: false,
+        }
+    }
+
+    pub(crate) fn jumped(&self) -> bool {
+        self.jumped
+    }
+
+    pub fn ip(&self) -> usize {
+        self.computer.mem.instruction_pointer
+    }
+
+    pub fn operation(&self) -> &Operation<W> {
+        self.op
+    }
+
+    pub fn param(&mut self, parameter: i64) -> Result<W, ComputerError<W>> {
+        self.computer.param(self.op, parameter)
+    }
+
+    pub fn store(&mut self, parameter: i64, value: W) -> Result<(), ComputerError<W>> {
+        self.computer.store(self.op, parameter, value)
+    }
+
+    pub fn read(&mut self, address: usize) -> Result<W, ComputerError<W>> {
+        self.computer.read_memory(self.op, address)
+    }
+
+    pub fn write(&mut self, address: usize, value: W) -> Result<(), ComputerError<W>> {
+        self.computer.write_memory(self.op, address, value)
+    }
+
+    pub fn relative_base(&self) -> i64 {
+        self.computer.mem.relative_base
+    }
+
+    pub fn set_relative_base(&mut self, base: i64) {
+        self.computer.mem.relative_base = base;
+    }
+
+    // Ok(None) when no input is available, return `StopReason::NeedsInput`
+    // from the handler to retry the instruction once there is.
+    pub fn input(&mut self) -> Result<Option<W>, ComputerError<W>> {
+        self.computer.read_input(self.op)
+    }
+
+    pub fn output(&mut self, value: W) -> Result<(), ComputerError<W>> {
+        self.computer.write_output(self.op, value)
+    }
+
+    pub fn jump(&mut self, target: W) -> Result<(), ComputerError<W>> {
+        self.computer.jump(self.op, target)?;
+        self.jumped = true;
+        Ok(())
+    }
+}
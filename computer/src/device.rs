@@ -0,0 +1,133 @@
+use super::Word;
+use std::any::Any;
+use std::fmt;
+use std::ops::Range;
+
+// Hardware mapped onto a range of addresses. Reads and writes made by instructions
+// to that range go to the device with the offset from the start of the range,
+// the paged memory underneath is left untouched. A write recorded in a `Step`
+// reads the device first so `step_back` can write the old value back to it.
+pub trait MemoryDevice<W = i64>: DeviceClone<W> + fmt::Debug + Send {
+    fn read(&mut self, offset: usize) -> W;
+    fn write(&mut self, offset: usize, value: W);
+}
+
+pub trait DeviceClone<W> {
+    fn clone_box(&self) -> Box<dyn MemoryDevice<W>>;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<W, T: MemoryDevice<W> + Clone + 'static> DeviceClone<W> for T {
+    fn clone_box(&self) -> Box<dyn MemoryDevice<W>> {
+        Box::new(self.clone())
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl<W> Clone for Box<dyn MemoryDevice<W>> {
+    fn clone(&self) -> Box<dyn MemoryDevice<W>> {
+        self.clone_box()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Devices<W = i64> {
+    mapped: Vec<(Range<usize>, Box<dyn MemoryDevice<W>>)>,
+}
+
+impl<W: Word> Devices<W> {
+    pub fn new() -> Devices<W> {
+        Devices { mapped: Vec::new() }
+    }
+
+    pub fn attach<T: MemoryDevice<W> + 'static>(&mut self, range: Range<usize>, device: T) {
+        assert!(
+            !self
+                .mapped
+                .iter()
+                .any(|(r, _)| r.start < range.end && range.start < r.end),
+            "device range {:?} overlaps another device",
+            range
+        );
+        self.mapped.push((range, Box::new(device)));
+    }
+
+    pub fn detach(&mut self, address: usize) -> Option<Box<dyn MemoryDevice<W>>> {
+        let index = self.mapped.iter().position(|(r, _)| r.contains(&address))?;
+        Some(self.mapped.remove(index).1)
+    }
+
+    pub fn device_as<T: MemoryDevice<W> + 'static>(&mut self) -> Option<&mut T> {
+        self.mapped
+            .iter_mut()
+            .find_map(|(_, device)| (**device).as_any_mut().downcast_mut::<T>())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mapped.is_empty()
+    }
+
+    pub fn read(&mut self, address: usize) -> Option<W> {
+        self.mapped
+            .iter_mut()
+            .find(|(r, _)| r.contains(&address))
+            .map(|(r, device)| device.read(address - r.start))
+    }
+
+    // Hands the value back when no device is mapped at `address`
+    pub fn write(&mut self, address: usize, value: W) -> Result<(), W> {
+        match self.mapped.iter_mut().find(|(r, _)| r.contains(&address)) {
+            Some((r, device)) => {
+                device.write(address - r.start, value);
+                Ok(())
+            }
+            None => Err(value),
+        }
+    }
+}
+
+impl<W: Word> Default for Devices<W> {
+    fn default() -> Devices<W> {
+        Devices::new()
+    }
+}
+
+#[derive(Clone)]
+pub struct FnDevice<R, F> {
+    read: R,
+    write: F,
+}
+
+impl<R, F> FnDevice<R, F> {
+    pub fn new<W>(read: R, write: F) -> FnDevice<R, F>
+    where
+        R: FnMut(usize) -> W,
+        F: FnMut(usize, W),
+    {
+        FnDevice { read, write }
+    }
+}
+
+impl<R, F> fmt::Debug for FnDevice<R, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("FnDevice")
+    }
+}
+
+impl<W, R, F> MemoryDevice<W> for FnDevice<R, F>
+where
+    W: Word,
+    R: FnMut(usize) -> W + Clone + Send + 'static,
+    F: FnMut(usize, W) + Clone + Send + 'static,
+{
+    fn read(&mut self, offset: usize) -> W {
+        (self.read)(offset)
+    }
+
+    fn write(&mut self, offset: usize, value: W) {
+        (self.write)(offset, value)
+    }
+}
@@ -0,0 +1,165 @@
+use super::{Access, Event, Word};
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ComputerError<W = i64> {
+    InvalidInteger {
+        position: usize,
+        value: String,
+    },
+    UnknownOpCode {
+        ip: usize,
+        opcode: W,
+    },
+    UnknownMode {
+        ip: usize,
+        opcode: W,
+        mode: i64,
+    },
+    InvalidParameter {
+        ip: usize,
+        opcode: W,
+        parameter: i64,
+    },
+    ImmediateWrite {
+        ip: usize,
+        opcode: W,
+    },
+    InvalidAddress {
+        ip: usize,
+        opcode: W,
+        address: W,
+    },
+    InvalidInput {
+        ip: usize,
+        opcode: W,
+        value: String,
+    },
+    Disconnected {
+        ip: usize,
+        opcode: W,
+    },
+    Overflow {
+        ip: usize,
+        opcode: W,
+        lhs: W,
+        rhs: W,
+        destination: usize,
+    },
+    ProtectionFault {
+        ip: usize,
+        opcode: W,
+        address: usize,
+        access: Access,
+    },
+    InvalidSnapshot {
+        reason: String,
+    },
+    TraceFailed {
+        ip: usize,
+        reason: String,
+    },
+    // `output` is None when the program asked for input instead
+    ReplayMismatch {
+        ip: usize,
+        instruction: u64,
+        expected: Option<Event<W>>,
+        output: Option<W>,
+    },
+}
+
+impl<W: Word> fmt::Display for ComputerError<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ComputerError::InvalidInteger { position, value } => {
+                write!(f, "invalid integer {:?} at position {}", value, position)
+            }
+            ComputerError::UnknownOpCode { ip, opcode } => {
+                write!(f, "unknown opcode {} at ip {}", opcode, ip)
+            }
+            ComputerError::UnknownMode { ip, opcode, mode } => write!(
+                f,
+                "unknown parameter mode {} in opcode {} at ip {}",
+                mode, opcode, ip
+            ),
+            ComputerError::InvalidParameter {
+                ip,
+                opcode,
+                parameter,
+            } => write!(
+                f,
+                "invalid parameter number {} for opcode {} at ip {}",
+                parameter, opcode, ip
+            ),
+            ComputerError::ImmediateWrite { ip, opcode } => write!(
+                f,
+                "cannot write to an immediate parameter in opcode {} at ip {}",
+                opcode, ip
+            ),
+            ComputerError::InvalidAddress {
+                ip,
+                opcode,
+                address,
+            } => write!(
+                f,
+                "invalid address {} accessed by opcode {} at ip {}",
+                address, opcode, ip
+            ),
+            ComputerError::InvalidInput { ip, opcode, value } => write!(
+                f,
+                "invalid input {:?} for opcode {} at ip {}",
+                value, opcode, ip
+            ),
+            ComputerError::Disconnected { ip, opcode } => {
+                write!(f, "io disconnected during opcode {} at ip {}", opcode, ip)
+            }
+            ComputerError::Overflow {
+                ip,
+                opcode,
+                lhs,
+                rhs,
+                destination,
+            } => write!(
+                f,
+                "overflow in opcode {} at ip {} with operands {} and {} writing to address {}",
+                opcode, ip, lhs, rhs, destination
+            ),
+            ComputerError::ProtectionFault {
+                ip,
+                opcode,
+                address,
+                access,
+            } => write!(
+                f,
+                "protection fault, {} of address {} by opcode {} at ip {}",
+                access, address, opcode, ip
+            ),
+            ComputerError::InvalidSnapshot { reason } => {
+                write!(f, "invalid snapshot: {}", reason)
+            }
+            ComputerError::TraceFailed { ip, reason } => {
+                write!(f, "failed to write trace at ip {}: {}", ip, reason)
+            }
+            ComputerError::ReplayMismatch {
+                ip,
+                instruction,
+                expected,
+                output,
+            } => {
+                write!(f, "replay diverged at ip {}, expected ", ip)?;
+                match expected {
+                    Some(event) => write!(f, "{}", event)?,
+                    None => write!(f, "the end of the transcript")?,
+                }
+                match output {
+                    Some(value) => write!(f, " but got output {}", value)?,
+                    None => write!(f, " but got an input request")?,
+                }
+                write!(f, " at instruction {}", instruction)
+            }
+        }
+    }
+}
+
+impl<W: Word> Error for ComputerError<W> {}
@@ -0,0 +1,150 @@
+use super::{Computer, ComputerError, IntcodeIo, IoError, StopReason, Word};
+use crossbeam_channel::{bounded, unbounded, Receiver, RecvTimeoutError, Sender};
+use std::panic::resume_unwind;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+// A computer running on its own thread, fed through `send` and read through `recv`.
+// It keeps going past outputs, breakpoints and input timeouts, and a closed channel
+// on either side stops it with `StopReason::Halted`. Instruction and time limits
+// still stop it early, the time limit counting from the spawn, and so does running
+// out of a `replay`.
+#[derive(Debug)]
+pub struct ComputerHandle<W = i64> {
+    input: Sender<W>,
+    output: Receiver<W>,
+    thread: JoinHandle<Result<Finished<W>, ComputerError<W>>>,
+}
+
+// What `ComputerHandle::join` hands back: the computer as it stopped, and any
+// outputs nobody received.
+#[derive(Debug)]
+pub struct Finished<W = i64> {
+    pub reason: StopReason<W>,
+    pub computer: Computer<W>,
+    pub outputs: Vec<W>,
+}
+
+// Unlike `ChannelIo` it only holds the computer's ends of the channels,
+// so it sees the handle dropping its own.
+#[derive(Debug, Clone)]
+struct ThreadIo<W> {
+    input: Receiver<W>,
+    output: Sender<W>,
+}
+
+impl<W: Word> IntcodeIo<W> for ThreadIo<W> {
+    fn read(&mut self) -> Result<Option<W>, IoError> {
+        self.input
+            .recv()
+            .map(Some)
+            .map_err(|_| IoError::Disconnected)
+    }
+
+    fn read_timeout(&mut self, timeout: Duration) -> Result<Option<W>, IoError> {
+        match self.input.recv_timeout(timeout) {
+            Ok(val) => Ok(Some(val)),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(IoError::Disconnected),
+        }
+    }
+
+    fn write(&mut self, value: W) -> Result<(), IoError> {
+        self.output.send(value).map_err(|_| IoError::Disconnected)
+    }
+}
+
+impl<W: Word> Computer<W> {
+    // The computer's own io is swapped for channels while it runs and put back before `join` returns it
+    pub fn spawn(mut self) -> ComputerHandle<W> {
+        let (input, computer_input) = bounded(1);
+        let (computer_output, output) = unbounded();
+
+        let thread = thread::spawn(move || {
+            let channels = ThreadIo {
+                input: computer_input,
+                output: computer_output,
+            };
+            let io = std::mem::replace(&mut self.io, Box::new(channels));
+            let result = self.run_to_end();
+            // Drops the channels, so `recv` stops waiting once the outputs are drained
+            self.io = io;
+            let reason = match result {
+                Ok(reason) => reason,
+                Err(ComputerError::Disconnected { .. }) => StopReason::Halted,
+                Err(e) => return Err(e),
+            };
+            Ok(Finished {
+                reason,
+                computer: self,
+                outputs: Vec::new(),
+            })
+        });
+
+        ComputerHandle {
+            input,
+            output,
+            thread,
+        }
+    }
+}
+
+impl<W: Word> Computer<W> {
+    fn run_to_end(&mut self) -> Result<StopReason<W>, ComputerError<W>> {
+        let deadline = self.deadline();
+        let mut result = self.run_before(deadline, |_| false);
+        loop {
+            result = match result {
+                Ok(StopReason::Output(_)) => self.run_before(deadline, |_| false),
+                // Only an input timeout is worth waiting on again, a finished replay never refills
+                Ok(StopReason::NeedsInput) if self.replay.is_none() && self.idle_reads > 0 => {
+                    self.run_before(deadline, |_| false)
+                }
+                Ok(StopReason::Breakpoint(_)) => self.execute().and_then(|reason| match reason {
+                    Some(reason) => Ok(reason),
+                    None => self.run_before(deadline, |_| false),
+                }),
+                _ => return result,
+            };
+        }
+    }
+}
+
+impl<W: Word> ComputerHandle<W> {
+    // Gives the value back if the computer has already stopped
+    pub fn send(&self, value: W) -> Result<(), W> {
+        self.input.send(value).map_err(|e| e.into_inner())
+    }
+
+    // `None` once the computer has stopped and every output was received
+    pub fn recv(&self) -> Option<W> {
+        self.output.recv().ok()
+    }
+
+    pub fn try_recv(&self) -> Option<W> {
+        self.output.try_recv().ok()
+    }
+
+    pub fn sender(&self) -> &Sender<W> {
+        &self.input
+    }
+
+    pub fn receiver(&self) -> &Receiver<W> {
+        &self.output
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    // Closes the input first, so a computer still waiting for input halts instead of blocking forever
+    pub fn join(self) -> Result<Finished<W>, ComputerError<W>> {
+        drop(self.input);
+        let mut finished = match self.thread.join() {
+            Ok(result) => result?,
+            Err(panic) => resume_unwind(panic),
+        };
+        finished.outputs = self.output.try_iter().collect();
+        Ok(finished)
+    }
+}
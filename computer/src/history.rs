@@ -0,0 +1,57 @@
+use super::{Step, Word};
+use std::collections::VecDeque;
+
+// The most recently executed instructions, oldest first. Once full the oldest
+// step is dropped, so memory use stays bounded by `capacity`.
+#[derive(Debug, Clone)]
+pub struct History<W = i64> {
+    steps: VecDeque<Step<W>>,
+    capacity: usize,
+}
+
+impl<W: Word> History<W> {
+    pub fn new(capacity: usize) -> History<W> {
+        History {
+            steps: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.steps.clear();
+    }
+
+    pub fn last(&self) -> Option<&Step<W>> {
+        self.steps.back()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Step<W>> {
+        self.steps.iter()
+    }
+
+    pub(crate) fn push(&mut self, step: Step<W>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.steps.len() == self.capacity {
+            self.steps.pop_front();
+        }
+        self.steps.push_back(step);
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<Step<W>> {
+        self.steps.pop_back()
+    }
+}
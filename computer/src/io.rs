@@ -0,0 +1,348 @@
+use super::{AsciiStdioIo, Word};
+use crossbeam_channel::{bounded, unbounded, Receiver, RecvTimeoutError, Sender};
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::{stdin, stdout, IsTerminal, Write};
+use std::time::Duration;
+
+pub type Buffer<W = i64> = VecDeque<W>;
+
+// How opcode 3 waits when no input is ready. `Computer::idle_reads` counts the
+// reads in a row that found nothing, whichever policy is used.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum InputPolicy<W = i64> {
+    // Wait for as long as the io does
+    #[default]
+    Block,
+    // Read this value instead
+    Default(W),
+    // Stop with `StopReason::NeedsInput` if nothing arrives in time
+    Timeout(Duration),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IoError {
+    InvalidInput(String),
+    Disconnected,
+}
+
+// A device the computer reads its inputs from and writes its outputs to.
+// Returning `Ok(None)` from `read` stops the computer with `StopReason::NeedsInput`
+// without consuming the instruction, so it can be resumed once input is available.
+pub trait IntcodeIo<W = i64>: IoClone<W> + fmt::Debug + Send {
+    fn read(&mut self) -> Result<Option<W>, IoError>;
+    fn write(&mut self, value: W) -> Result<(), IoError>;
+
+    // Like `read` but gives up with `Ok(None)` once `timeout` passes. Devices that
+    // never block can leave it as `read`.
+    fn read_timeout(&mut self, _timeout: Duration) -> Result<Option<W>, IoError> {
+        self.read()
+    }
+
+    // Called when `Computer::step_back` undoes an instruction that read or wrote
+    // `value`, devices that can take it back should do so.
+    fn unread(&mut self, _value: W) {}
+    fn unwrite(&mut self, _value: W) {}
+}
+
+pub trait IoClone<W> {
+    fn clone_box(&self) -> Box<dyn IntcodeIo<W>>;
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<W, T: IntcodeIo<W> + Clone + 'static> IoClone<W> for T {
+    fn clone_box(&self) -> Box<dyn IntcodeIo<W>> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl<W> Clone for Box<dyn IntcodeIo<W>> {
+    fn clone(&self) -> Box<dyn IntcodeIo<W>> {
+        self.clone_box()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum IOMode {
+    Buffer,
+    Stdio,
+    AsciiStdio,
+    Channel,
+}
+
+impl IOMode {
+    pub fn into_io<W: Word>(self) -> Box<dyn IntcodeIo<W>> {
+        match self {
+            IOMode::Buffer => Box::new(BufferIo::default()),
+            IOMode::Stdio => Box::new(StdioIo::new()),
+            IOMode::AsciiStdio => Box::new(AsciiStdioIo::new()),
+            IOMode::Channel => Box::new(ChannelIo::new()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BufferIo<W = i64> {
+    pub input: Buffer<W>,
+    pub output: Buffer<W>,
+}
+
+impl<W> Default for BufferIo<W> {
+    fn default() -> BufferIo<W> {
+        BufferIo {
+            input: Buffer::new(),
+            output: Buffer::new(),
+        }
+    }
+}
+
+impl<W: Word> IntcodeIo<W> for BufferIo<W> {
+    fn read(&mut self) -> Result<Option<W>, IoError> {
+        Ok(self.input.pop_front())
+    }
+
+    fn write(&mut self, value: W) -> Result<(), IoError> {
+        self.output.push_back(value);
+        Ok(())
+    }
+
+    fn unread(&mut self, value: W) {
+        self.input.push_front(value);
+    }
+
+    fn unwrite(&mut self, value: W) {
+        if self.output.back() == Some(&value) {
+            self.output.pop_back();
+        }
+    }
+}
+
+// Prompts for input and labels outputs when stdin is a terminal. When it's piped
+// the prompts are left out and outputs are printed one per line, so runs can be
+// scripted. A line can hold several values separated by commas or whitespace.
+#[derive(Debug, Clone)]
+pub struct StdioIo<W = i64> {
+    pub interactive: bool,
+    pending: Buffer<W>,
+}
+
+impl<W: Word> StdioIo<W> {
+    pub fn new() -> StdioIo<W> {
+        StdioIo {
+            interactive: stdin().is_terminal(),
+            pending: Buffer::new(),
+        }
+    }
+
+    // Queues the values on `line` to be read before asking stdin for more
+    pub fn push_line(&mut self, line: &str) -> Result<(), IoError> {
+        for value in line
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|v| !v.is_empty())
+        {
+            match value.parse::<W>() {
+                Ok(v) => self.pending.push_back(v),
+                Err(..) => return Err(IoError::InvalidInput(value.to_string())),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<W: Word> Default for StdioIo<W> {
+    fn default() -> StdioIo<W> {
+        StdioIo::new()
+    }
+}
+
+impl<W: Word> IntcodeIo<W> for StdioIo<W> {
+    fn read(&mut self) -> Result<Option<W>, IoError> {
+        while self.pending.is_empty() {
+            if self.interactive {
+                print!("Please enter a number: ");
+            }
+            let _ = stdout().flush();
+
+            let mut s = String::new();
+            match stdin().read_line(&mut s) {
+                Ok(0) => return Ok(None),
+                Ok(_) => self.push_line(&s)?,
+                Err(e) => return Err(IoError::InvalidInput(e.to_string())),
+            }
+        }
+        Ok(self.pending.pop_front())
+    }
+
+    fn write(&mut self, value: W) -> Result<(), IoError> {
+        match self.interactive {
+            true => println!("Output: {}", value),
+            false => println!("{}", value),
+        }
+        Ok(())
+    }
+
+    fn unread(&mut self, value: W) {
+        self.pending.push_front(value);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Channel<W = i64> {
+    pub sender: Sender<W>,
+    pub receiver: Receiver<W>,
+}
+
+impl<W> Channel<W> {
+    pub fn new(bound: Option<usize>) -> Channel<W> {
+        let (sender, receiver) = match bound {
+            Some(b) => bounded(b),
+            None => unbounded(),
+        };
+        Channel { sender, receiver }
+    }
+
+    // Gives the value back once every receiver has been dropped
+    pub fn send(&self, val: W) -> Result<(), W> {
+        self.sender.send(val).map_err(|e| e.into_inner())
+    }
+
+    // `None` once every sender has been dropped and the channel is empty
+    pub fn recv(&self) -> Option<W> {
+        self.receiver.recv().ok()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChannelIo<W = i64> {
+    pub input: Channel<W>,
+    pub output: Channel<W>,
+}
+
+impl<W> ChannelIo<W> {
+    pub fn new() -> ChannelIo<W> {
+        ChannelIo {
+            input: Channel::new(Some(1)),
+            output: Channel::new(None),
+        }
+    }
+}
+
+impl<W> Default for ChannelIo<W> {
+    fn default() -> ChannelIo<W> {
+        ChannelIo::new()
+    }
+}
+
+impl<W: Word> IntcodeIo<W> for ChannelIo<W> {
+    fn read(&mut self) -> Result<Option<W>, IoError> {
+        match self.input.receiver.recv() {
+            Ok(val) => Ok(Some(val)),
+            Err(..) => Err(IoError::Disconnected),
+        }
+    }
+
+    fn read_timeout(&mut self, timeout: Duration) -> Result<Option<W>, IoError> {
+        match self.input.receiver.recv_timeout(timeout) {
+            Ok(val) => Ok(Some(val)),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(IoError::Disconnected),
+        }
+    }
+
+    fn write(&mut self, value: W) -> Result<(), IoError> {
+        self.output
+            .sender
+            .send(value)
+            .map_err(|_| IoError::Disconnected)
+    }
+}
+
+#[derive(Clone)]
+pub struct FnIo<I, O> {
+    input: I,
+    output: O,
+}
+
+impl<I, O> FnIo<I, O> {
+    pub fn new<W>(input: I, output: O) -> FnIo<I, O>
+    where
+        I: FnMut() -> Option<W>,
+        O: FnMut(W),
+    {
+        FnIo { input, output }
+    }
+}
+
+impl<I, O> fmt::Debug for FnIo<I, O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("FnIo")
+    }
+}
+
+impl<W, I, O> IntcodeIo<W> for FnIo<I, O>
+where
+    W: Word,
+    I: FnMut() -> Option<W> + Clone + Send + 'static,
+    O: FnMut(W) + Clone + Send + 'static,
+{
+    fn read(&mut self) -> Result<Option<W>, IoError> {
+        Ok((self.input)())
+    }
+
+    fn write(&mut self, value: W) -> Result<(), IoError> {
+        (self.output)(value);
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct IterIo<I: Iterator> {
+    input: I,
+    pub output: Buffer<I::Item>,
+}
+
+impl<I: Iterator> IterIo<I> {
+    pub fn new<T: IntoIterator<IntoIter = I>>(input: T) -> IterIo<I> {
+        IterIo {
+            input: input.into_iter(),
+            output: Buffer::new(),
+        }
+    }
+}
+
+impl<I: Iterator> fmt::Debug for IterIo<I>
+where
+    I::Item: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("IterIo")
+            .field("output", &self.output)
+            .finish()
+    }
+}
+
+impl<W, I> IntcodeIo<W> for IterIo<I>
+where
+    W: Word,
+    I: Iterator<Item = W> + Clone + Send + 'static,
+{
+    fn read(&mut self) -> Result<Option<W>, IoError> {
+        Ok(self.input.next())
+    }
+
+    fn write(&mut self, value: W) -> Result<(), IoError> {
+        self.output.push_back(value);
+        Ok(())
+    }
+}
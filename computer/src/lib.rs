@@ -1,240 +1,1045 @@
-#[macro_use]
-extern crate lazy_static;
-
-use crossbeam_channel::bounded;
-use crossbeam_channel::{unbounded, Receiver, Sender};
-
+pub use self::ascii::{AsciiOutput, AsciiStdioIo};
+#[cfg(feature = "async")]
+pub use self::async_io::ASYNC_SLICE;
+pub use self::coverage::{Coverage, Mark};
+pub use self::custom::{Instruction, OpCodeRegistry, MAX_CUSTOM_PARAMETERS};
+pub use self::device::{DeviceClone, Devices, FnDevice, MemoryDevice};
+pub use self::error::ComputerError;
+pub use self::handle::{ComputerHandle, Finished};
+pub use self::history::History;
+pub use self::io::{
+    Buffer, BufferIo, Channel, ChannelIo, FnIo, IOMode, InputPolicy, IntcodeIo, IoClone, IoError,
+    IterIo, StdioIo,
+};
 pub use self::operation::Mode;
 pub use self::operation::OpCode;
 pub use self::operation::Operation;
+pub use self::paged::PagedMemory;
+pub use self::profiler::{AddressCount, JumpCount, Loop, OpCodeCount, ProfileReport, Profiler};
+pub use self::protection::{Access, MemoryProtection, Protection};
+pub use self::snapshot::Snapshot;
+pub use self::step::{MemoryWrite, Step};
+pub use self::tracer::{TraceRecord, Tracer};
+pub use self::transcript::{Event, Replay, Transcript};
+pub use self::word::Word;
+#[cfg(feature = "bigint")]
+pub use num_bigint::BigInt;
+mod ascii;
+#[cfg(feature = "async")]
+mod async_io;
+mod cache;
+mod coverage;
+mod custom;
+mod device;
+mod error;
+mod handle;
+mod history;
+mod io;
 mod operation;
+mod paged;
+mod profiler;
+mod protection;
+mod snapshot;
+mod step;
+mod tracer;
+mod transcript;
+mod word;
 
-use std::collections::VecDeque;
-use std::io::{stdin, stdout, Write};
+use self::cache::DecodeCache;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+use tracing::{debug, trace, warn};
 
-#[derive(Debug, Clone)]
-pub enum IOMode {
-    Buffer,
-    Stdio,
-    Channel,
-}
+const TIME_CHECK_INTERVAL: u64 = 1024;
 
-type Buffer = VecDeque<i64>;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StopReason<W = i64> {
+    Halted,
+    NeedsInput,
+    Output(W),
+    Breakpoint(usize),
+    InstructionLimit(u64),
+    TimeLimit(u64),
+}
 
 #[derive(Debug, Clone)]
-pub struct Memory {
-    pub input_buffer: Buffer,
-    pub output_buffer: Buffer,
-    pub memory: Vec<i64>,
+pub struct Memory<W = i64> {
+    // Code written here directly stays decoded as it was, call
+    // `Computer::clear_decode_cache` after changing it
+    pub memory: PagedMemory<W>,
     pub relative_base: i64,
     pub instruction_pointer: usize,
+    pub protection: MemoryProtection,
+    pub devices: Devices<W>,
+    image: PagedMemory<W>,
+    decoded: DecodeCache<W>,
 }
 
-impl Memory {
-    fn get_mode<'a>(&mut self, op: &'a Operation, parameter: i64) -> &'a Mode {
+impl<W: Word> Memory<W> {
+    fn get_mode<'a>(
+        &self,
+        op: &'a Operation<W>,
+        parameter: i64,
+    ) -> Result<&'a Mode, ComputerError<W>> {
         match parameter {
-            1 => &op.modes.0,
-            2 => &op.modes.1,
-            3 => &op.modes.2,
-            _ => panic!("unknown paramater number, must be 1 2 or 3"),
+            1 => Ok(&op.modes.0),
+            2 => Ok(&op.modes.1),
+            3 => Ok(&op.modes.2),
+            _ => Err(ComputerError::InvalidParameter {
+                ip: self.instruction_pointer,
+                opcode: op.data[0].clone(),
+                parameter,
+            }),
         }
     }
-    fn set(&mut self, op: &Operation, parameter: i64, value: i64) {
-        let addr = op.data[parameter as usize];
-        match self.get_mode(op, parameter) {
-            Mode::Immediate => panic!("Cannot set immediately!"),
-            Mode::Position => self.memory[addr as usize] = value,
-            Mode::Relative => self.memory[(addr + self.relative_base) as usize] = value,
+
+    fn get_data<'a>(
+        &self,
+        op: &'a Operation<W>,
+        parameter: i64,
+    ) -> Result<&'a W, ComputerError<W>> {
+        op.data
+            .get(parameter as usize)
+            .ok_or_else(|| ComputerError::InvalidParameter {
+                ip: self.instruction_pointer,
+                opcode: op.data[0].clone(),
+                parameter,
+            })
+    }
+
+    fn check_address(
+        &self,
+        op: &Operation<W>,
+        address: &W,
+        offset: i64,
+    ) -> Result<usize, ComputerError<W>> {
+        match address.to_i64().and_then(|a| a.checked_add(offset)) {
+            Some(a) if a >= 0 => Ok(a as usize),
+            _ => Err(ComputerError::InvalidAddress {
+                ip: self.instruction_pointer,
+                opcode: op.data[0].clone(),
+                address: address.wrapping_add(&W::from_i64(offset)),
+            }),
         }
     }
 
-    fn get(&mut self, op: &Operation, parameter: i64) -> i64 {
-        let v = op.data[parameter as usize];
+    fn check_access(
+        &self,
+        op: &Operation<W>,
+        address: usize,
+        access: Access,
+    ) -> Result<usize, ComputerError<W>> {
+        match self.protection.check(address..address + 1, access) {
+            None => Ok(address),
+            Some(address) => Err(ComputerError::ProtectionFault {
+                ip: self.instruction_pointer,
+                opcode: op.data[0].clone(),
+                address,
+                access,
+            }),
+        }
+    }
 
-        match self.get_mode(op, parameter) {
-            Mode::Immediate => v,
-            Mode::Position => self.memory[v as usize],
-            Mode::Relative => self.memory[(v + self.relative_base) as usize],
+    fn destination(&self, op: &Operation<W>, parameter: i64) -> Result<usize, ComputerError<W>> {
+        let addr = self.get_data(op, parameter)?;
+        match self.get_mode(op, parameter)? {
+            Mode::Immediate => Err(ComputerError::ImmediateWrite {
+                ip: self.instruction_pointer,
+                opcode: op.data[0].clone(),
+            }),
+            Mode::Position => self.check_address(op, addr, 0),
+            Mode::Relative => self.check_address(op, addr, self.relative_base),
         }
     }
 
-    fn from_string(input: &str) -> Memory {
-        let mut extramem = (0..65536).map(|_| 0).collect::<Vec<i64>>();
+    // Also gives the address read from, unless the parameter is immediate
+    fn get(
+        &mut self,
+        op: &Operation<W>,
+        parameter: i64,
+    ) -> Result<(W, Option<usize>), ComputerError<W>> {
+        let v = self.get_data(op, parameter)?;
 
-        let mut m = Memory {
-            memory: input
-                .split(",")
-                .map(|s| s.parse().unwrap())
-                .collect::<Vec<i64>>(),
-            input_buffer: VecDeque::new(),
-            output_buffer: VecDeque::new(),
-            relative_base: 0,
-            instruction_pointer: 0,
+        let addr = match self.get_mode(op, parameter)? {
+            Mode::Immediate => return Ok((v.clone(), None)),
+            Mode::Position => self.check_address(op, v, 0)?,
+            Mode::Relative => self.check_address(op, v, self.relative_base)?,
         };
-        m.memory.append(&mut extramem);
-        m
+        let value = self.read_cell(self.check_access(op, addr, Access::Read)?);
+        Ok((value, Some(addr)))
     }
-}
 
-#[derive(Debug, Clone)]
-pub struct Channel {
-    pub sender: Sender<i64>,
-    pub receiver: Receiver<i64>,
-}
+    fn read_cell(&mut self, address: usize) -> W {
+        match self.devices.read(address) {
+            Some(value) => value,
+            None => self.memory.read(address),
+        }
+    }
 
-impl Channel {
-    fn new(bound: Option<usize>) -> Channel {
-        let (sender, receiver) = match bound {
-            Some(b) => bounded(b),
-            None => unbounded(),
-        };
-        Channel { sender, receiver }
+    fn write_cell(&mut self, address: usize, value: W) {
+        if let Err(value) = self.devices.write(address, value) {
+            self.memory.write(address, value);
+            self.decoded.invalidate(address);
+        }
     }
 
-    pub fn send(&self, val: i64) {
-        self.sender.send(val).unwrap()
+    fn new(memory: PagedMemory<W>) -> Memory<W> {
+        Memory {
+            image: memory.clone(),
+            memory,
+            relative_base: 0,
+            instruction_pointer: 0,
+            protection: MemoryProtection::new(),
+            devices: Devices::new(),
+            decoded: DecodeCache::new(),
+        }
     }
 
-    pub fn recv(&self) -> i64 {
-        self.receiver.recv().unwrap()
+    fn from_string(input: &str) -> Result<Memory<W>, ComputerError<W>> {
+        let memory = input
+            .trim()
+            .split(',')
+            .enumerate()
+            .map(|(position, s)| {
+                s.trim().parse().map_err(|_| ComputerError::InvalidInteger {
+                    position,
+                    value: s.to_string(),
+                })
+            })
+            .collect::<Result<Vec<W>, ComputerError<W>>>()?
+            .into();
+        Ok(Memory::new(memory))
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct Computer {
-    pub iomode: IOMode,
-    pub log_prefix: String,
-    pub enable_logger: bool,
-    pub mem: Memory,
-    pub input_channel: Channel,
-    pub output_channel: Channel,
+pub struct Computer<W = i64> {
+    pub io: Box<dyn IntcodeIo<W>>,
+    // Attached to every event the computer logs, to tell apart several running at once.
+    // Events go through `tracing`: instructions and writes at trace, io and stops at
+    // debug, and errors at warn.
+    pub name: String,
+    pub break_on_output: bool,
+    pub breakpoints: HashSet<usize>,
+    pub instruction_limit: Option<u64>,
+    pub time_limit: Option<Duration>,
+    pub instruction_count: u64,
+    pub decode_cache: bool,
+    // Report overflow in add and mul as an error instead of wrapping
+    pub checked_arithmetic: bool,
+    opcodes: OpCodeRegistry<W>,
+    // Set to record executed instructions so they can be undone with `step_back`
+    pub history: Option<History<W>>,
+    // Every value read or written is appended to `recorder` when set. While `replay` is set
+    // inputs come from it instead of io, and outputs must match the ones it recorded.
+    pub recorder: Option<Transcript<W>>,
+    pub replay: Option<Replay<W>>,
+    // Writes a JSON line for each executed instruction when set
+    pub tracer: Option<Tracer>,
+    pub profiler: Option<Profiler>,
+    pub coverage: Option<Coverage>,
+    pub input_policy: InputPolicy<W>,
+    // Reads in a row that found no input, reset by the next one that does
+    pub idle_reads: u64,
+    pub mem: Memory<W>,
+    trace: Option<Step<W>>,
 }
 
+// `from_string` and `with_io` build the usual i64 computer,
+// `Computer::<W>::load` and `load_with_io` pick another word type.
 impl Computer {
-    pub fn from_string(input: &str, iomode: IOMode) -> Computer {
+    pub fn from_string(input: &str, iomode: IOMode) -> Result<Computer, ComputerError> {
+        Computer::load(input, iomode)
+    }
+
+    pub fn with_io<T: IntcodeIo + 'static>(input: &str, io: T) -> Result<Computer, ComputerError> {
+        Computer::load_with_io(input, io)
+    }
+}
+
+impl<W: Word> Computer<W> {
+    pub fn load(input: &str, iomode: IOMode) -> Result<Computer<W>, ComputerError<W>> {
+        Computer::from_boxed_io(input, iomode.into_io())
+    }
+
+    pub fn load_with_io<T: IntcodeIo<W> + 'static>(
+        input: &str,
+        io: T,
+    ) -> Result<Computer<W>, ComputerError<W>> {
+        Computer::from_boxed_io(input, Box::new(io))
+    }
+
+    // The snapshot becomes the image `reset` returns to
+    pub fn from_snapshot(snapshot: Snapshot<W>, iomode: IOMode) -> Computer<W> {
+        let mut c = Computer::new(Memory::new(snapshot.memory.clone()), iomode.into_io());
+        c.restore(snapshot);
+        c
+    }
+
+    fn from_boxed_io(
+        input: &str,
+        io: Box<dyn IntcodeIo<W>>,
+    ) -> Result<Computer<W>, ComputerError<W>> {
+        Ok(Computer::new(Memory::from_string(input)?, io))
+    }
+
+    fn new(mem: Memory<W>, io: Box<dyn IntcodeIo<W>>) -> Computer<W> {
         Computer {
-            mem: Memory::from_string(input),
-            iomode: iomode,
-            log_prefix: "".to_string(),
-            enable_logger: false,
-            input_channel: Channel::new(Some(1)),
-            output_channel: Channel::new(None),
+            mem,
+            io,
+            name: "".to_string(),
+            break_on_output: false,
+            breakpoints: HashSet::new(),
+            instruction_limit: None,
+            time_limit: None,
+            instruction_count: 0,
+            decode_cache: true,
+            checked_arithmetic: false,
+            opcodes: OpCodeRegistry::new(),
+            history: None,
+            recorder: None,
+            replay: None,
+            tracer: None,
+            profiler: None,
+            coverage: None,
+            input_policy: InputPolicy::Block,
+            idle_reads: 0,
+            trace: None,
         }
     }
 
-    pub fn run(&mut self) {
+    pub fn run(&mut self) -> Result<StopReason<W>, ComputerError<W>> {
+        self.run_until(|_| false)
+    }
+
+    // Stops before executing any instruction at a breakpoint or where `condition` holds.
+    // Calling `run` again from a breakpoint stops straight away, use `resume` to step over it.
+    // `instruction_limit` counts across runs, `time_limit` applies to each call.
+    pub fn run_until<F>(&mut self, condition: F) -> Result<StopReason<W>, ComputerError<W>>
+    where
+        F: FnMut(&Computer<W>) -> bool,
+    {
+        let deadline = self.deadline();
+        self.run_before(deadline, condition)
+    }
+
+    // The time limit counting from now
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.time_limit.map(|limit| Instant::now() + limit)
+    }
+
+    // Like `run_until` but stopping at a deadline that several runs can share
+    pub(crate) fn run_before<F>(
+        &mut self,
+        deadline: Option<Instant>,
+        mut condition: F,
+    ) -> Result<StopReason<W>, ComputerError<W>>
+    where
+        F: FnMut(&Computer<W>) -> bool,
+    {
+        let mut iterations: u64 = 0;
         loop {
-            let op = Operation::from_computer(self);
-            let length = Operation::get_length(&op.op_code);
-            let orig_ip = self.mem.instruction_pointer;
-
-            if self.enable_logger {
-                //todo: log nicely
-                self.log(format!("{:?}\tOutBuf:[{:?}]", op, self.mem.output_buffer));
-            }
-
-            match op.op_code {
-                OpCode::Add => self.add(op),
-                OpCode::Mul => self.mul(op),
-                OpCode::Input => self.input(op),
-                OpCode::Output => self.output(op),
-                OpCode::JumpIfTrue => self.jump_if_true(op),
-                OpCode::JumpIfFalse => self.jump_if_false(op),
-                OpCode::Lessthan => self.less_than(op),
-                OpCode::Equals => self.equals(op),
-                OpCode::OffsetBase => self.offset_base(op),
-                OpCode::End => {
-                    break;
+            let ip = self.mem.instruction_pointer;
+            if (!self.breakpoints.is_empty() && self.breakpoints.contains(&ip)) || condition(self) {
+                return Ok(StopReason::Breakpoint(ip));
+            }
+            if let Some(limit) = self.instruction_limit {
+                if self.instruction_count >= limit && !self.is_completed() {
+                    return Ok(StopReason::InstructionLimit(self.instruction_count));
+                }
+            }
+            if let Some(deadline) = deadline {
+                if iterations.is_multiple_of(TIME_CHECK_INTERVAL) && Instant::now() >= deadline {
+                    return Ok(StopReason::TimeLimit(self.instruction_count));
+                }
+                iterations += 1;
+            }
+            match self.execute() {
+                Ok(Some(reason)) => {
+                    debug!(name = %self.name, ip = self.mem.instruction_pointer, reason = ?reason, "stopped");
+                    return Ok(reason);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    warn!(name = %self.name, ip = self.mem.instruction_pointer, error = %e, "failed");
+                    return Err(e);
                 }
-            };
-            if self.mem.instruction_pointer == orig_ip {
-                self.increment_ip(length);
             }
         }
     }
 
-    fn log(&mut self, str: String) {
-        println!("{} -> {}", self.log_prefix, str);
+    pub fn resume(&mut self) -> Result<StopReason<W>, ComputerError<W>> {
+        match self.execute()? {
+            Some(reason) => Ok(reason),
+            None => self.run(),
+        }
+    }
+
+    pub fn step(&mut self) -> Result<Step<W>, ComputerError<W>> {
+        let count = self.instruction_count;
+        let op = self.decode()?;
+        self.trace = Some(Step::new(
+            self.mem.instruction_pointer,
+            self.mem.relative_base,
+            (*op).clone(),
+        ));
+        let reason = self.execute_operation(&op);
+        let mut step = self.trace.take().expect("step trace missing");
+        self.retire(step.ip, op);
+        step.stop = reason?;
+        self.account(step.ip, step.operation.op_code, &step.stop);
+        // Only instructions that ran count, whatever they stopped with
+        if let Some(history) = self.history.as_mut() {
+            if self.instruction_count > count {
+                history.push(step.clone());
+            }
+        }
+        if let Some(tracer) = self.tracer.as_ref() {
+            // An instruction waiting for input runs again once it arrives
+            if step.stop != Some(StopReason::NeedsInput) {
+                tracer
+                    .record(&TraceRecord::new(count, &step))
+                    .map_err(|e| ComputerError::TraceFailed {
+                        ip: step.ip,
+                        reason: e.to_string(),
+                    })?;
+            }
+        }
+        Ok(step)
+    }
+
+    // Undoes the most recent instruction in the history, None when there is nothing to undo
+    pub fn step_back(&mut self) -> Option<Step<W>> {
+        let step = self.history.as_mut()?.pop()?;
+        for write in step.writes.iter().rev() {
+            self.mem.write_cell(write.address, write.old.clone());
+        }
+        self.instruction_count -= 1;
+        let instruction = self.instruction_count;
+        if let Some(output) = step.output.clone() {
+            self.io.unwrite(output.clone());
+            if let (true, Some(replay)) = (step.replayed, self.replay.as_mut()) {
+                replay.put_back(Event::Output {
+                    instruction,
+                    value: output,
+                });
+            }
+        }
+        match (step.input.clone(), self.replay.as_mut()) {
+            (Some(value), Some(replay)) if step.replayed => {
+                replay.put_back(Event::Input { instruction, value })
+            }
+            // A default has nothing to give back to the io
+            (Some(input), _) if !step.defaulted => self.io.unread(input),
+            _ => {}
+        }
+        if let Some(recorder) = self.recorder.as_mut() {
+            while recorder.events.last().map(Event::instruction) >= Some(instruction) {
+                recorder.events.pop();
+            }
+        }
+        self.mem.instruction_pointer = step.ip;
+        self.mem.relative_base = step.relative_base;
+        Some(step)
+    }
+
+    // Steps back until reaching a breakpoint, returning its address,
+    // or None if the history runs out first
+    pub fn run_back(&mut self) -> Option<usize> {
+        self.run_back_until(|_| false)
+    }
+
+    pub fn run_back_until<F>(&mut self, mut condition: F) -> Option<usize>
+    where
+        F: FnMut(&Computer<W>) -> bool,
+    {
+        while self.step_back().is_some() {
+            let ip = self.mem.instruction_pointer;
+            if (!self.breakpoints.is_empty() && self.breakpoints.contains(&ip)) || condition(self) {
+                return Some(ip);
+            }
+        }
+        None
+    }
+
+    // Steps back until `instruction_count` is `n`. Returns false, without changing
+    // anything, if the history doesn't reach back that far.
+    pub fn rewind_to(&mut self, n: u64) -> bool {
+        let recorded = self.history.as_ref().map_or(0, |h| h.len() as u64);
+        if n > self.instruction_count || self.instruction_count - n > recorded {
+            return false;
+        }
+        while self.instruction_count > n {
+            self.step_back();
+        }
+        true
+    }
+
+    fn execute(&mut self) -> Result<Option<StopReason<W>>, ComputerError<W>> {
+        if self.history.is_some() || self.tracer.is_some() {
+            return Ok(self.step()?.stop);
+        }
+        let ip = self.mem.instruction_pointer;
+        let op = self.decode()?;
+        let reason = self.execute_operation(&op);
+        let op_code = op.op_code;
+        self.retire(ip, op);
+        let reason = reason?;
+        self.account(ip, op_code, &reason);
+        Ok(reason)
+    }
+
+    // Counts an executed instruction in the profiler and coverage
+    fn account(&mut self, ip: usize, op_code: OpCode, reason: &Option<StopReason<W>>) {
+        if self.profiler.is_none() && self.coverage.is_none() {
+            return;
+        }
+        if *reason == Some(StopReason::NeedsInput) {
+            return;
+        }
+        let length = Operation::<W>::get_length(&op_code);
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.record(ip, op_code, self.mem.instruction_pointer);
+        }
+        if let Some(coverage) = self.coverage.as_mut() {
+            coverage.execute(ip, length);
+        }
+    }
+
+    // Takes the instruction at the instruction pointer out of the decode cache, or decodes
+    // it. `retire` hands it back once it has run.
+    fn decode(&mut self) -> Result<Box<Operation<W>>, ComputerError<W>> {
+        let ip = self.mem.instruction_pointer;
+        self.check_execute(ip..ip + 1)?;
+        let cached = match self.decode_cache {
+            true => self.mem.decoded.take(ip),
+            false => None,
+        };
+        let op = match cached {
+            Some(op) => op,
+            None => {
+                let op = Box::new(Operation::from_computer(self)?);
+                self.mem.decoded.start(ip, op.data.len());
+                op
+            }
+        };
+        if let Err(e) = self.check_execute(ip + 1..ip + op.data.len()) {
+            self.retire(ip, op);
+            return Err(e);
+        }
+        Ok(op)
+    }
+
+    fn retire(&mut self, ip: usize, op: Box<Operation<W>>) {
+        if self.decode_cache {
+            self.mem.decoded.put_back(ip, op);
+        }
+    }
+
+    fn check_execute(&self, range: Range<usize>) -> Result<(), ComputerError<W>> {
+        match self.mem.protection.check(range, Access::Execute) {
+            None => Ok(()),
+            Some(address) => Err(ComputerError::ProtectionFault {
+                ip: self.mem.instruction_pointer,
+                opcode: self.mem.memory.read(self.mem.instruction_pointer),
+                address,
+                access: Access::Execute,
+            }),
+        }
+    }
+
+    // Puts the memory and registers back to how the program was loaded, without
+    // reparsing it. Breakpoints, limits, protection, devices and io are left as they are.
+    pub fn reset(&mut self) {
+        self.mem.memory = self.mem.image.clone();
+        self.mem.instruction_pointer = 0;
+        self.mem.relative_base = 0;
+        self.mem.decoded.clear();
+        self.instruction_count = 0;
+        self.idle_reads = 0;
+        if let Some(history) = self.history.as_mut() {
+            history.clear();
+        }
+    }
+
+    // A copy that shares memory with this computer until either writes to it. Its history
+    // and decode cache start out empty, so it can't step back past the fork.
+    pub fn fork(&self) -> Computer<W> {
+        Computer {
+            io: self.io.clone(),
+            name: self.name.clone(),
+            break_on_output: self.break_on_output,
+            breakpoints: self.breakpoints.clone(),
+            instruction_limit: self.instruction_limit,
+            time_limit: self.time_limit,
+            instruction_count: self.instruction_count,
+            decode_cache: self.decode_cache,
+            checked_arithmetic: self.checked_arithmetic,
+            opcodes: self.opcodes.clone(),
+            history: self.history.as_ref().map(|h| History::new(h.capacity())),
+            recorder: self.recorder.clone(),
+            replay: self.replay.clone(),
+            tracer: self.tracer.clone(),
+            profiler: self.profiler.clone(),
+            coverage: self.coverage.clone(),
+            input_policy: self.input_policy.clone(),
+            idle_reads: self.idle_reads,
+            mem: Memory {
+                memory: self.mem.memory.clone(),
+                relative_base: self.mem.relative_base,
+                instruction_pointer: self.mem.instruction_pointer,
+                protection: self.mem.protection.clone(),
+                devices: self.mem.devices.clone(),
+                image: self.mem.image.clone(),
+                decoded: DecodeCache::new(),
+            },
+            trace: None,
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot<W> {
+        Snapshot {
+            memory: self.mem.memory.clone(),
+            instruction_pointer: self.mem.instruction_pointer,
+            relative_base: self.mem.relative_base,
+            instruction_count: self.instruction_count,
+            buffers: (*self.io).as_any().downcast_ref::<BufferIo<W>>().cloned(),
+        }
+    }
+
+    // Buffers in the snapshot are only restored when this computer uses buffer io
+    pub fn restore(&mut self, snapshot: Snapshot<W>) {
+        self.mem.memory = snapshot.memory;
+        self.mem.instruction_pointer = snapshot.instruction_pointer;
+        self.mem.relative_base = snapshot.relative_base;
+        self.mem.decoded.clear();
+        self.instruction_count = snapshot.instruction_count;
+        if let Some(history) = self.history.as_mut() {
+            history.clear();
+        }
+        if let (Some(buffers), Some(io)) = (snapshot.buffers, self.io_as::<BufferIo<W>>()) {
+            *io = buffers;
+        }
+    }
+
+    pub fn clear_decode_cache(&mut self) {
+        self.mem.decoded.clear();
+    }
+
+    fn execute_operation(
+        &mut self,
+        op: &Operation<W>,
+    ) -> Result<Option<StopReason<W>>, ComputerError<W>> {
+        let length = Operation::<W>::get_length(&op.op_code);
+        let op_code = op.op_code;
+
+        trace!(
+            name = %self.name,
+            ip = self.mem.instruction_pointer,
+            opcode = ?op.op_code,
+            operands = ?&op.data[1..],
+            relative_base = self.mem.relative_base,
+            "execute"
+        );
+
+        let reason = match op.op_code {
+            OpCode::Add => self.add(op)?,
+            OpCode::Mul => self.mul(op)?,
+            OpCode::Input => self.input(op)?,
+            OpCode::Output => self.output(op)?,
+            OpCode::JumpIfTrue => self.jump_if_true(op)?,
+            OpCode::JumpIfFalse => self.jump_if_false(op)?,
+            OpCode::Lessthan => self.less_than(op)?,
+            OpCode::Equals => self.equals(op)?,
+            OpCode::OffsetBase => self.offset_base(op)?,
+            OpCode::Custom { code, .. } => self.custom(op, code)?,
+            OpCode::End => return Ok(Some(StopReason::Halted)),
+        };
+        if reason == Some(StopReason::NeedsInput) {
+            return Ok(reason);
+        }
+        match op_code {
+            OpCode::JumpIfTrue | OpCode::JumpIfFalse | OpCode::Custom { .. } => {}
+            _ => self.increment_ip(length),
+        }
+        self.instruction_count += 1;
+        Ok(reason)
+    }
+
+    pub fn io_as<T: IntcodeIo<W> + 'static>(&mut self) -> Option<&mut T> {
+        (*self.io).as_any_mut().downcast_mut::<T>()
+    }
+
+    pub fn buffer_io(&mut self) -> &mut BufferIo<W> {
+        self.try_buffer_io()
+            .expect("Computer is not using buffer io")
+    }
+
+    pub fn try_buffer_io(&mut self) -> Option<&mut BufferIo<W>> {
+        self.io_as::<BufferIo<W>>()
+    }
+
+    pub fn input_to_buffer(&mut self, val: W) {
+        self.buffer_io().input.push_back(val);
+    }
+
+    // Hands the value back when the computer isn't using buffer io
+    pub fn try_input_to_buffer(&mut self, val: W) -> Result<(), W> {
+        match self.try_buffer_io() {
+            Some(buffer) => {
+                buffer.input.push_back(val);
+                Ok(())
+            }
+            None => Err(val),
+        }
+    }
+
+    pub fn output_from_buffer(&mut self) -> W {
+        self.try_output_from_buffer().expect("Output buffer empty")
+    }
+
+    // None when the output buffer is empty or the computer isn't using buffer io
+    pub fn try_output_from_buffer(&mut self) -> Option<W> {
+        self.try_buffer_io()?.output.pop_front()
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.is_completed()
     }
 
     fn increment_ip(&mut self, size: usize) {
         self.mem.instruction_pointer += size;
     }
 
-    pub fn is_completed(&mut self) -> bool {
-        self.mem.memory[self.mem.instruction_pointer] == 99
+    pub fn is_completed(&self) -> bool {
+        self.mem.memory[self.mem.instruction_pointer].to_i64() == Some(99)
+    }
+
+    fn io_error(&self, op: &Operation<W>, err: IoError) -> ComputerError<W> {
+        let ip = self.mem.instruction_pointer;
+        let opcode = op.data[0].clone();
+        match err {
+            IoError::InvalidInput(value) => ComputerError::InvalidInput { ip, opcode, value },
+            IoError::Disconnected => ComputerError::Disconnected { ip, opcode },
+        }
+    }
+
+    fn invalid_address(&self, op: &Operation<W>, address: W) -> ComputerError<W> {
+        ComputerError::InvalidAddress {
+            ip: self.mem.instruction_pointer,
+            opcode: op.data[0].clone(),
+            address,
+        }
+    }
+
+    fn param(&mut self, op: &Operation<W>, parameter: i64) -> Result<W, ComputerError<W>> {
+        let (val, address) = self.mem.get(op, parameter)?;
+        if let (Some(coverage), Some(address)) = (self.coverage.as_mut(), address) {
+            coverage.read(address);
+        }
+        if let Some(step) = self.trace.as_mut() {
+            step.operands.push(val.clone());
+        }
+        Ok(val)
+    }
+
+    fn store(
+        &mut self,
+        op: &Operation<W>,
+        parameter: i64,
+        value: W,
+    ) -> Result<(), ComputerError<W>> {
+        let address = self.mem.destination(op, parameter)?;
+        self.write_memory(op, address, value)
+    }
+
+    // Also writes to addresses chosen by a custom opcode rather than one of its parameters
+    fn write_memory(
+        &mut self,
+        op: &Operation<W>,
+        address: usize,
+        value: W,
+    ) -> Result<(), ComputerError<W>> {
+        self.mem.check_access(op, address, Access::Write)?;
+        // Reading a device can have side effects, so the old value is only read for a step
+        let old = match self.trace {
+            Some(_) => Some(self.mem.read_cell(address)),
+            None => None,
+        };
+        self.mem.write_cell(address, value.clone());
+        if let Some(coverage) = self.coverage.as_mut() {
+            coverage.write(address);
+        }
+        trace!(
+            name = %self.name,
+            ip = self.mem.instruction_pointer,
+            address = address,
+            value = %value,
+            "write"
+        );
+        if let (Some(step), Some(old)) = (self.trace.as_mut(), old) {
+            step.writes.push(MemoryWrite {
+                address,
+                old,
+                new: value,
+            });
+        }
+        Ok(())
+    }
+
+    fn read_memory(&mut self, op: &Operation<W>, address: usize) -> Result<W, ComputerError<W>> {
+        let address = self.mem.check_access(op, address, Access::Read)?;
+        if let Some(coverage) = self.coverage.as_mut() {
+            coverage.read(address);
+        }
+        Ok(self.mem.read_cell(address))
     }
 
-    fn read_stdin() -> i64 {
-        let mut s = String::new();
-        print!("Please enter a number: ");
-        let _ = stdout().flush();
-        stdin().read_line(&mut s).expect("incorrect number");
+    fn jump(&mut self, op: &Operation<W>, target: W) -> Result<(), ComputerError<W>> {
+        match target.to_i64() {
+            Some(t) if t >= 0 => self.mem.instruction_pointer = t as usize,
+            _ => return Err(self.invalid_address(op, target)),
+        }
+        Ok(())
+    }
 
-        let trimmed = s.trim();
-        match trimmed.parse::<i64>() {
-            Ok(i) => return i,
-            Err(..) => panic!("Not an integer: {}", trimmed),
+    fn offset_base(
+        &mut self,
+        op: &Operation<W>,
+    ) -> Result<Option<StopReason<W>>, ComputerError<W>> {
+        let offset = self.param(op, 1)?;
+        match offset
+            .to_i64()
+            .and_then(|o| self.mem.relative_base.checked_add(o))
+        {
+            Some(base) => self.mem.relative_base = base,
+            None => return Err(self.invalid_address(op, offset)),
         }
+        Ok(None)
     }
 
-    fn offset_base(&mut self, op: Operation) {
-        self.mem.relative_base += self.mem.get(&op, 1);
+    fn read_input(&mut self, op: &Operation<W>) -> Result<Option<W>, ComputerError<W>> {
+        let val = match self.replay.is_some() {
+            true => self.replay_input()?,
+            false => self.poll_input(op)?,
+        };
+        let (val, from_io) = match (val, &self.input_policy) {
+            (Some(val), _) => (val, true),
+            (None, InputPolicy::Default(val)) if self.replay.is_none() => (val.clone(), false),
+            (None, _) => return Ok(None),
+        };
+        if let Some(step) = self.trace.as_mut() {
+            step.input = Some(val.clone());
+            step.defaulted = !from_io;
+            step.replayed = self.replay.is_some();
+        }
+        debug!(name = %self.name, ip = self.mem.instruction_pointer, value = %val, "input");
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.events.push(Event::Input {
+                instruction: self.instruction_count,
+                value: val.clone(),
+            });
+        }
+        Ok(Some(val))
     }
 
-    fn input<'a>(&mut self, op: Operation) {
-        let val = match self.iomode {
-            IOMode::Stdio => Computer::read_stdin(),
-            IOMode::Buffer => self
-                .mem
-                .input_buffer
-                .pop_front()
-                .expect("Input buffer empty"),
-            IOMode::Channel => self.input_channel.receiver.recv().unwrap(),
+    fn poll_input(&mut self, op: &Operation<W>) -> Result<Option<W>, ComputerError<W>> {
+        let read = match &self.input_policy {
+            InputPolicy::Block => self.io.read(),
+            InputPolicy::Default(_) => self.io.read_timeout(Duration::from_secs(0)),
+            InputPolicy::Timeout(timeout) => self.io.read_timeout(*timeout),
         };
-        self.mem.set(&op, 1, val);
+        match read {
+            Ok(Some(val)) => {
+                self.idle_reads = 0;
+                Ok(Some(val))
+            }
+            Ok(None) => {
+                self.idle_reads += 1;
+                Ok(None)
+            }
+            Err(e) => Err(self.io_error(op, e)),
+        }
+    }
+
+    fn write_output(&mut self, op: &Operation<W>, val: W) -> Result<(), ComputerError<W>> {
+        if self.replay.is_some() {
+            self.replay_output(&val)?;
+        }
+        if let Some(step) = self.trace.as_mut() {
+            step.output = Some(val.clone());
+            step.replayed = self.replay.is_some();
+        }
+        debug!(name = %self.name, ip = self.mem.instruction_pointer, value = %val, "output");
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.events.push(Event::Output {
+                instruction: self.instruction_count,
+                value: val.clone(),
+            });
+        }
+        self.io.write(val).map_err(|e| self.io_error(op, e))
     }
 
-    fn output<'a>(&mut self, op: Operation) {
-        let val = self.mem.get(&op, 1);
-        match self.iomode {
-            IOMode::Stdio => println!("Output: {}", val),
-            IOMode::Buffer => self.mem.output_buffer.push_back(val),
-            IOMode::Channel => self.output_channel.sender.send(val).unwrap(),
+    // An input is only replayed at the instruction it was recorded at,
+    // running out of events stops the computer waiting for input.
+    fn replay_input(&mut self) -> Result<Option<W>, ComputerError<W>> {
+        let instruction = self.instruction_count;
+        let replay = self.replay.as_mut().expect("replay missing");
+        match replay.peek().cloned() {
+            None => Ok(None),
+            Some(Event::Input {
+                instruction: recorded,
+                value,
+            }) if recorded == instruction => {
+                replay.pop();
+                Ok(Some(value))
+            }
+            expected => Err(self.replay_mismatch(expected, None)),
+        }
+    }
+
+    fn replay_output(&mut self, val: &W) -> Result<(), ComputerError<W>> {
+        let actual = Event::Output {
+            instruction: self.instruction_count,
+            value: val.clone(),
         };
+        let replay = self.replay.as_mut().expect("replay missing");
+        if replay.peek() == Some(&actual) {
+            replay.pop();
+            return Ok(());
+        }
+        let expected = replay.peek().cloned();
+        Err(self.replay_mismatch(expected, Some(val.clone())))
+    }
+
+    fn replay_mismatch(&self, expected: Option<Event<W>>, output: Option<W>) -> ComputerError<W> {
+        ComputerError::ReplayMismatch {
+            ip: self.mem.instruction_pointer,
+            instruction: self.instruction_count,
+            expected,
+            output,
+        }
+    }
+
+    fn custom(
+        &mut self,
+        op: &Operation<W>,
+        code: i64,
+    ) -> Result<Option<StopReason<W>>, ComputerError<W>> {
+        let handler = self
+            .opcodes
+            .handler(code)
+            .ok_or_else(|| ComputerError::UnknownOpCode {
+                ip: self.mem.instruction_pointer,
+                opcode: op.data[0].clone(),
+            })?;
+        let mut instruction = Instruction::new(self, op);
+        let reason = handler(&mut instruction)?;
+        let jumped = instruction.jumped();
+        if reason != Some(StopReason::NeedsInput) && !jumped {
+            self.increment_ip(op.data.len());
+        }
+        Ok(reason)
     }
 
-    fn add(&mut self, op: Operation) {
-        let res = self.mem.get(&op, 1) + self.mem.get(&op, 2);
-        self.mem.set(&op, 3, res);
+    fn input(&mut self, op: &Operation<W>) -> Result<Option<StopReason<W>>, ComputerError<W>> {
+        let val = match self.read_input(op)? {
+            Some(val) => val,
+            None => return Ok(Some(StopReason::NeedsInput)),
+        };
+        self.store(op, 1, val)?;
+        Ok(None)
     }
 
-    fn mul(&mut self, op: Operation) {
-        let res = self.mem.get(&op, 1) * self.mem.get(&op, 2);
-        self.mem.set(&op, 3, res);
+    fn output(&mut self, op: &Operation<W>) -> Result<Option<StopReason<W>>, ComputerError<W>> {
+        let val = self.param(op, 1)?;
+        self.write_output(op, val.clone())?;
+        match self.break_on_output {
+            true => Ok(Some(StopReason::Output(val))),
+            false => Ok(None),
+        }
     }
 
-    fn jump_if_true(&mut self, op: Operation) {
-        match self.mem.get(&op, 1) != 0 {
-            true => self.mem.instruction_pointer = self.mem.get(&op, 2) as usize,
-            false => self.increment_ip(Operation::get_length(&op.op_code)),
+    fn arithmetic(
+        &mut self,
+        op: &Operation<W>,
+        wrapping: fn(&W, &W) -> W,
+        checked: fn(&W, &W) -> Option<W>,
+    ) -> Result<W, ComputerError<W>> {
+        let lhs = self.param(op, 1)?;
+        let rhs = self.param(op, 2)?;
+        if !self.checked_arithmetic {
+            return Ok(wrapping(&lhs, &rhs));
+        }
+        match checked(&lhs, &rhs) {
+            Some(res) => Ok(res),
+            None => Err(ComputerError::Overflow {
+                ip: self.mem.instruction_pointer,
+                opcode: op.data[0].clone(),
+                destination: self.mem.destination(op, 3)?,
+                lhs,
+                rhs,
+            }),
         }
     }
 
-    fn jump_if_false(&mut self, op: Operation) {
-        match self.mem.get(&op, 1) == 0 {
-            true => self.mem.instruction_pointer = self.mem.get(&op, 2) as usize,
-            false => self.increment_ip(Operation::get_length(&op.op_code)),
+    fn add(&mut self, op: &Operation<W>) -> Result<Option<StopReason<W>>, ComputerError<W>> {
+        let res = self.arithmetic(op, W::wrapping_add, W::checked_add)?;
+        self.store(op, 3, res)?;
+        Ok(None)
+    }
+
+    fn mul(&mut self, op: &Operation<W>) -> Result<Option<StopReason<W>>, ComputerError<W>> {
+        let res = self.arithmetic(op, W::wrapping_mul, W::checked_mul)?;
+        self.store(op, 3, res)?;
+        Ok(None)
+    }
+
+    fn jump_if_true(
+        &mut self,
+        op: &Operation<W>,
+    ) -> Result<Option<StopReason<W>>, ComputerError<W>> {
+        let taken = !self.param(op, 1)?.is_zero();
+        self.branch(op, taken)
+    }
+
+    fn jump_if_false(
+        &mut self,
+        op: &Operation<W>,
+    ) -> Result<Option<StopReason<W>>, ComputerError<W>> {
+        let taken = self.param(op, 1)?.is_zero();
+        self.branch(op, taken)
+    }
+
+    fn branch(
+        &mut self,
+        op: &Operation<W>,
+        taken: bool,
+    ) -> Result<Option<StopReason<W>>, ComputerError<W>> {
+        let ip = self.mem.instruction_pointer;
+        match taken {
+            true => {
+                let target = self.param(op, 2)?;
+                self.jump(op, target)?
+            }
+            false => self.increment_ip(Operation::<W>::get_length(&op.op_code)),
+        }
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.record_jump(ip, taken);
         }
+        Ok(None)
     }
 
-    fn less_than(&mut self, op: Operation) {
-        let res = self.mem.get(&op, 1) < self.mem.get(&op, 2);
-        self.mem.set(&op, 3, if res == true { 1 } else { 0 });
+    fn less_than(&mut self, op: &Operation<W>) -> Result<Option<StopReason<W>>, ComputerError<W>> {
+        let res = self.param(op, 1)? < self.param(op, 2)?;
+        self.store(op, 3, W::from_i64(if res { 1 } else { 0 }))?;
+        Ok(None)
     }
 
-    fn equals(&mut self, op: Operation) {
-        let res = self.mem.get(&op, 1) == self.mem.get(&op, 2);
-        self.mem.set(&op, 3, if res == true { 1 } else { 0 });
+    fn equals(&mut self, op: &Operation<W>) -> Result<Option<StopReason<W>>, ComputerError<W>> {
+        let res = self.param(op, 1)? == self.param(op, 2)?;
+        self.store(op, 3, W::from_i64(if res { 1 } else { 0 }))?;
+        Ok(None)
     }
 }
@@ -1,9 +1,7 @@
-use super::Computer;
-use num_enum::TryFromPrimitive;
-use std::collections::HashMap;
-use std::convert::TryFrom;
+use super::{Computer, ComputerError, Word};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Mode {
     Position,
     Immediate,
@@ -11,76 +9,105 @@ pub enum Mode {
 }
 
 impl Mode {
-    pub fn from_i64(input: i64) -> Mode {
+    pub fn from_i64(input: i64) -> Option<Mode> {
         match input {
-            0 => Mode::Position,
-            1 => Mode::Immediate,
-            2 => Mode::Relative,
-            _ => panic!("Unknown parameter mode {}", input),
+            0 => Some(Mode::Position),
+            1 => Some(Mode::Immediate),
+            2 => Some(Mode::Relative),
+            _ => None,
         }
     }
 }
 
-#[derive(Debug, TryFromPrimitive, PartialEq, Eq, Hash)]
-#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum OpCode {
-    Add = 1,
-    Mul = 2,
-    Input = 3,
-    Output = 4,
-    JumpIfTrue = 5,
-    JumpIfFalse = 6,
-    Lessthan = 7,
-    Equals = 8,
-    OffsetBase = 9,
-    End = 99,
+    Add,
+    Mul,
+    Input,
+    Output,
+    JumpIfTrue,
+    JumpIfFalse,
+    Lessthan,
+    Equals,
+    OffsetBase,
+    End,
+    // Registered with `Computer::register_opcode`, only decoded when no built-in opcode matches
+    Custom { code: i64, parameters: usize },
+}
+
+impl OpCode {
+    pub fn from_i64(input: i64) -> Option<OpCode> {
+        match input {
+            1 => Some(OpCode::Add),
+            2 => Some(OpCode::Mul),
+            3 => Some(OpCode::Input),
+            4 => Some(OpCode::Output),
+            5 => Some(OpCode::JumpIfTrue),
+            6 => Some(OpCode::JumpIfFalse),
+            7 => Some(OpCode::Lessthan),
+            8 => Some(OpCode::Equals),
+            9 => Some(OpCode::OffsetBase),
+            99 => Some(OpCode::End),
+            _ => None,
+        }
+    }
 }
 
 type Modes = (Mode, Mode, Mode);
 
-#[derive(Debug, PartialEq)]
-pub struct Operation {
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operation<W = i64> {
     pub op_code: OpCode,
     pub modes: Modes,
-    pub data: Vec<i64>,
+    pub data: Vec<W>,
 }
 
-impl Operation {
+impl<W: Word> Operation<W> {
     pub fn get_length(opcode: &OpCode) -> usize {
-        lazy_static! {
-            static ref HASHMAP: HashMap<OpCode, usize> = {
-                let mut m = HashMap::new();
-                m.insert(OpCode::Add, 4);
-                m.insert(OpCode::Mul, 4);
-                m.insert(OpCode::Input, 2);
-                m.insert(OpCode::Output, 2);
-                m.insert(OpCode::JumpIfTrue, 3);
-                m.insert(OpCode::JumpIfFalse, 3);
-                m.insert(OpCode::Lessthan, 4);
-                m.insert(OpCode::Equals, 4);
-                m.insert(OpCode::End, 1);
-                m.insert(OpCode::OffsetBase, 2);
-                m
-            };
+        match opcode {
+            OpCode::Add => 4,
+            OpCode::Mul => 4,
+            OpCode::Input => 2,
+            OpCode::Output => 2,
+            OpCode::JumpIfTrue => 3,
+            OpCode::JumpIfFalse => 3,
+            OpCode::Lessthan => 4,
+            OpCode::Equals => 4,
+            OpCode::End => 1,
+            OpCode::OffsetBase => 2,
+            OpCode::Custom { parameters, .. } => parameters + 1,
         }
-        *HASHMAP.get(opcode).unwrap()
     }
 
-    pub fn from_computer(computer: &Computer) -> Operation {
-        let raw_opcode = computer.mem.memory[computer.mem.instruction_pointer];
-        let opcode = OpCode::try_from((raw_opcode % 100) as i32).expect("Failed to parse opcode");
-        let length = Operation::get_length(&opcode);
-        let data = computer.mem.memory
-            [computer.mem.instruction_pointer..computer.mem.instruction_pointer + length]
-            .to_vec();
-        Operation {
+    pub fn from_computer(computer: &Computer<W>) -> Result<Operation<W>, ComputerError<W>> {
+        let ip = computer.mem.instruction_pointer;
+        let raw = computer.mem.memory.read(ip);
+        let unknown = || ComputerError::UnknownOpCode {
+            ip,
+            opcode: raw.clone(),
+        };
+        let raw_opcode = raw.to_i64().ok_or_else(unknown)?;
+        let code = raw_opcode % 100;
+        let opcode = OpCode::from_i64(code)
+            .or_else(|| computer.opcodes.opcode(code))
+            .ok_or_else(unknown)?;
+        let length = Operation::<W>::get_length(&opcode);
+        let data = computer.mem.memory.read_range(ip..ip + length);
+        let mode = |mode: i64| {
+            Mode::from_i64(mode).ok_or(ComputerError::UnknownMode {
+                ip,
+                opcode: raw.clone(),
+                mode,
+            })
+        };
+        Ok(Operation {
             op_code: opcode,
             modes: (
-                Mode::from_i64(raw_opcode / 100 % 10),
-                Mode::from_i64(raw_opcode / 1000 % 10),
-                Mode::from_i64(raw_opcode / 10000 % 10),
+                mode(raw_opcode / 100 % 10)?,
+                mode(raw_opcode / 1000 % 10)?,
+                mode(raw_opcode / 10000 % 10)?,
             ),
             data,
-        }
+        })
     }
 }
@@ -0,0 +1,116 @@
+use super::Word;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ops::{Index, IndexMut, Range};
+use std::sync::Arc;
+
+pub const PAGE_SIZE: usize = 1024;
+
+type Page<W> = Arc<[W]>;
+
+// Pages are only allocated when first written, unmapped cells read as 0.
+// Clones share their pages until one side writes to them (copy-on-write).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "W: Word"))]
+pub struct PagedMemory<W = i64> {
+    pages: BTreeMap<usize, Page<W>>,
+    len: usize,
+    #[serde(skip, default = "zero")]
+    zero: W,
+}
+
+fn zero<W: Word>() -> W {
+    W::from_i64(0)
+}
+
+impl<W: Word> PagedMemory<W> {
+    pub fn new() -> PagedMemory<W> {
+        PagedMemory {
+            pages: BTreeMap::new(),
+            len: 0,
+            zero: zero(),
+        }
+    }
+
+    pub fn read(&self, address: usize) -> W {
+        self[address].clone()
+    }
+
+    pub fn write(&mut self, address: usize, value: W) {
+        self[address] = value;
+    }
+
+    pub fn read_range(&self, range: Range<usize>) -> Vec<W> {
+        range.map(|address| self.read(address)).collect()
+    }
+
+    // One past the highest address that has been written to.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn allocated_pages(&self) -> usize {
+        self.pages.len()
+    }
+
+    // Deserialized memory may not have come from a `PagedMemory`
+    pub(crate) fn is_consistent(&self) -> bool {
+        self.pages.values().all(|page| page.len() == PAGE_SIZE)
+            && self
+                .pages
+                .keys()
+                .next_back()
+                .map_or(self.len == 0, |last| (last + 1) * PAGE_SIZE >= self.len)
+    }
+
+    // Pages not shared with any clone of this memory
+    pub fn unique_pages(&self) -> usize {
+        self.pages
+            .values()
+            .filter(|page| Arc::strong_count(page) == 1)
+            .count()
+    }
+}
+
+impl<W: Word> Default for PagedMemory<W> {
+    fn default() -> PagedMemory<W> {
+        PagedMemory::new()
+    }
+}
+
+impl<W: Word> From<Vec<W>> for PagedMemory<W> {
+    fn from(values: Vec<W>) -> PagedMemory<W> {
+        let mut m = PagedMemory::new();
+        for (address, value) in values.into_iter().enumerate() {
+            m.write(address, value);
+        }
+        m
+    }
+}
+
+impl<W: Word> Index<usize> for PagedMemory<W> {
+    type Output = W;
+
+    fn index(&self, address: usize) -> &W {
+        match self.pages.get(&(address / PAGE_SIZE)) {
+            Some(page) => &page[address % PAGE_SIZE],
+            None => &self.zero,
+        }
+    }
+}
+
+impl<W: Word> IndexMut<usize> for PagedMemory<W> {
+    fn index_mut(&mut self, address: usize) -> &mut W {
+        self.len = self.len.max(address + 1);
+        let zero = &self.zero;
+        let page = self
+            .pages
+            .entry(address / PAGE_SIZE)
+            .or_insert_with(|| vec![zero.clone(); PAGE_SIZE].into());
+        &mut Arc::make_mut(page)[address % PAGE_SIZE]
+    }
+}
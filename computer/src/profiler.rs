@@ -0,0 +1,182 @@
+use super::OpCode;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+// Counts what a computer executes while set as its `profiler`. Counts carry on
+// across runs and resets until `clear` is called.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Profiler {
+    addresses: BTreeMap<usize, u64>,
+    opcodes: BTreeMap<OpCode, u64>,
+    // (taken, not taken) for each conditional jump
+    jumps: BTreeMap<usize, (u64, u64)>,
+    // Jumps back to or before themselves, keyed by (from, to)
+    back_edges: BTreeMap<(usize, usize), u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressCount {
+    pub address: usize,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpCodeCount {
+    pub opcode: OpCode,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JumpCount {
+    pub address: usize,
+    pub taken: u64,
+    pub not_taken: u64,
+}
+
+// A back-edge from the instruction at `from` to `to`, taken `count` times
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Loop {
+    pub from: usize,
+    pub to: usize,
+    pub count: u64,
+}
+
+// Everything but the addresses is listed in full, most executed first
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileReport {
+    pub instructions: u64,
+    pub top_addresses: Vec<AddressCount>,
+    pub opcodes: Vec<OpCodeCount>,
+    pub jumps: Vec<JumpCount>,
+    pub loops: Vec<Loop>,
+}
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler::default()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Profiler::new();
+    }
+
+    pub fn instructions(&self) -> u64 {
+        self.addresses.values().sum()
+    }
+
+    pub fn count_at(&self, address: usize) -> u64 {
+        self.addresses.get(&address).copied().unwrap_or(0)
+    }
+
+    pub fn count_of(&self, opcode: OpCode) -> u64 {
+        self.opcodes.get(&opcode).copied().unwrap_or(0)
+    }
+
+    // `next` is the instruction pointer once the instruction at `ip` has run
+    pub(crate) fn record(&mut self, ip: usize, opcode: OpCode, next: usize) {
+        *self.addresses.entry(ip).or_insert(0) += 1;
+        *self.opcodes.entry(opcode).or_insert(0) += 1;
+        // `End` leaves the instruction pointer where it was
+        if next <= ip && opcode != OpCode::End {
+            *self.back_edges.entry((ip, next)).or_insert(0) += 1;
+        }
+    }
+
+    // Counted from the condition, a jump to the next instruction is still taken
+    pub(crate) fn record_jump(&mut self, ip: usize, taken: bool) {
+        let jump = self.jumps.entry(ip).or_insert((0, 0));
+        match taken {
+            true => jump.0 += 1,
+            false => jump.1 += 1,
+        }
+    }
+
+    pub fn report(&self, top: usize) -> ProfileReport {
+        let mut top_addresses: Vec<_> = self
+            .addresses
+            .iter()
+            .map(|(&address, &count)| AddressCount { address, count })
+            .collect();
+        top_addresses.sort_by(|a, b| b.count.cmp(&a.count).then(a.address.cmp(&b.address)));
+        top_addresses.truncate(top);
+
+        let mut opcodes: Vec<_> = self
+            .opcodes
+            .iter()
+            .map(|(&opcode, &count)| OpCodeCount { opcode, count })
+            .collect();
+        opcodes.sort_by(|a, b| b.count.cmp(&a.count).then(a.opcode.cmp(&b.opcode)));
+
+        let mut jumps: Vec<_> = self
+            .jumps
+            .iter()
+            .map(|(&address, &(taken, not_taken))| JumpCount {
+                address,
+                taken,
+                not_taken,
+            })
+            .collect();
+        jumps.sort_by_key(|j| std::cmp::Reverse(j.taken + j.not_taken));
+
+        let mut loops: Vec<_> = self
+            .back_edges
+            .iter()
+            .map(|(&(from, to), &count)| Loop { from, to, count })
+            .collect();
+        loops.sort_by_key(|l| std::cmp::Reverse(l.count));
+
+        ProfileReport {
+            instructions: self.instructions(),
+            top_addresses,
+            opcodes,
+            jumps,
+            loops,
+        }
+    }
+}
+
+impl ProfileReport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("profile report is always serializable")
+    }
+}
+
+impl fmt::Display for ProfileReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let share = |count: u64| match self.instructions {
+            0 => 0.0,
+            total => count as f64 * 100.0 / total as f64,
+        };
+
+        writeln!(f, "{} instructions executed", self.instructions)?;
+        writeln!(f, "top addresses:")?;
+        for a in &self.top_addresses {
+            writeln!(
+                f,
+                "  {:>6} {:>12} {:>6.2}%",
+                a.address,
+                a.count,
+                share(a.count)
+            )?;
+        }
+        writeln!(f, "opcodes:")?;
+        for o in &self.opcodes {
+            let name = format!("{:?}", o.opcode);
+            writeln!(f, "  {:<12} {:>12} {:>6.2}%", name, o.count, share(o.count))?;
+        }
+        writeln!(f, "jumps:")?;
+        for j in &self.jumps {
+            writeln!(
+                f,
+                "  {:>6} taken {:>10} not taken {:>10}",
+                j.address, j.taken, j.not_taken
+            )?;
+        }
+        writeln!(f, "loops:")?;
+        for l in &self.loops {
+            writeln!(f, "  {:>6} -> {:<6} {:>12}", l.from, l.to, l.count)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use std::fmt;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protection {
+    ReadOnly,
+    WriteOnly,
+    NoExecute,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+    Execute,
+}
+
+impl Protection {
+    fn allows(self, access: Access) -> bool {
+        match self {
+            Protection::ReadOnly => access != Access::Write,
+            Protection::WriteOnly => access == Access::Write,
+            Protection::NoExecute => access != Access::Execute,
+        }
+    }
+}
+
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Access::Read => "read",
+            Access::Write => "write",
+            Access::Execute => "execute",
+        })
+    }
+}
+
+// Regions may overlap, an access is refused if any region covering the address refuses it.
+// Executing an instruction checks every word it was decoded from.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryProtection {
+    regions: Vec<(Range<usize>, Protection)>,
+}
+
+impl MemoryProtection {
+    pub fn new() -> MemoryProtection {
+        MemoryProtection::default()
+    }
+
+    pub fn protect(&mut self, range: Range<usize>, protection: Protection) {
+        self.regions.push((range, protection));
+    }
+
+    pub fn clear(&mut self) {
+        self.regions.clear();
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    // The first address in `range` that refuses `access`
+    #[inline]
+    pub fn check(&self, range: Range<usize>, access: Access) -> Option<usize> {
+        if self.regions.is_empty() {
+            return None;
+        }
+        range.into_iter().find(|address| {
+            self.regions
+                .iter()
+                .any(|(region, protection)| region.contains(address) && !protection.allows(access))
+        })
+    }
+}
@@ -0,0 +1,55 @@
+use super::{BufferIo, ComputerError, PagedMemory, Word};
+use bincode::Options;
+use serde::{Deserialize, Serialize};
+
+// The state needed to resume a computer exactly where it left off. Pending input and
+// output are only captured for buffer io, other devices can't be read back.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(serialize = "W: Serialize", deserialize = "W: Word"))]
+pub struct Snapshot<W = i64> {
+    pub memory: PagedMemory<W>,
+    pub instruction_pointer: usize,
+    pub relative_base: i64,
+    pub instruction_count: u64,
+    pub buffers: Option<BufferIo<W>>,
+}
+
+impl<W: Word> Snapshot<W> {
+    pub fn to_json(&self) -> Result<String, ComputerError<W>> {
+        serde_json::to_string(self).map_err(invalid)
+    }
+
+    pub fn from_json(json: &str) -> Result<Snapshot<W>, ComputerError<W>> {
+        serde_json::from_str::<Snapshot<W>>(json)
+            .map_err(invalid)?
+            .check()
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ComputerError<W>> {
+        bincode::DefaultOptions::new()
+            .serialize(self)
+            .map_err(invalid)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Snapshot<W>, ComputerError<W>> {
+        bincode::DefaultOptions::new()
+            .deserialize::<Snapshot<W>>(bytes)
+            .map_err(invalid)?
+            .check()
+    }
+
+    fn check(self) -> Result<Snapshot<W>, ComputerError<W>> {
+        match self.memory.is_consistent() {
+            true => Ok(self),
+            false => Err(ComputerError::InvalidSnapshot {
+                reason: "memory pages are malformed".to_string(),
+            }),
+        }
+    }
+}
+
+fn invalid<W, E: ToString>(err: E) -> ComputerError<W> {
+    ComputerError::InvalidSnapshot {
+        reason: err.to_string(),
+    }
+}
@@ -0,0 +1,44 @@
+use super::{Operation, StopReason, Word};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryWrite<W = i64> {
+    pub address: usize,
+    pub old: W,
+    pub new: W,
+}
+
+// Everything a single executed instruction did, as returned by `Computer::step`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Step<W = i64> {
+    pub ip: usize,
+    // The base the instruction's relative parameters were resolved against
+    pub relative_base: i64,
+    pub operation: Operation<W>,
+    pub operands: Vec<W>,
+    pub writes: Vec<MemoryWrite<W>>,
+    pub input: Option<W>,
+    // Set when `input` came from `InputPolicy::Default` rather than the io
+    pub defaulted: bool,
+    // Set when `input` or `output` was taken from `Computer::replay`
+    pub replayed: bool,
+    pub output: Option<W>,
+    pub stop: Option<StopReason<W>>,
+}
+
+impl<W: Word> Step<W> {
+    pub fn new(ip: usize, relative_base: i64, operation: Operation<W>) -> Step<W> {
+        Step {
+            ip,
+            relative_base,
+            operation,
+            operands: Vec::new(),
+            writes: Vec::new(),
+            input: None,
+            defaulted: false,
+            replayed: false,
+            output: None,
+            stop: None,
+        }
+    }
+}
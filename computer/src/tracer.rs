@@ -0,0 +1,81 @@
+use super::{MemoryWrite, Mode, OpCode, Step, Word};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+// One line of a trace, describing an executed instruction. `step` is the computer's
+// `instruction_count` before it ran and `operands` are the parameter values it read.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(bound(deserialize = "W: Word"))]
+pub struct TraceRecord<W = i64> {
+    pub step: u64,
+    pub ip: usize,
+    pub raw_opcode: W,
+    pub opcode: OpCode,
+    pub modes: (Mode, Mode, Mode),
+    pub operands: Vec<W>,
+    pub writes: Vec<MemoryWrite<W>>,
+    pub relative_base: i64,
+    pub input: Option<W>,
+    // Set when `input` came from `InputPolicy::Default` rather than the io
+    #[serde(default)]
+    pub defaulted: bool,
+    pub output: Option<W>,
+}
+
+impl<W: Word> TraceRecord<W> {
+    pub fn new(step: u64, executed: &Step<W>) -> TraceRecord<W> {
+        let operation = &executed.operation;
+        TraceRecord {
+            step,
+            ip: executed.ip,
+            raw_opcode: operation.data[0].clone(),
+            opcode: operation.op_code,
+            modes: operation.modes,
+            operands: executed.operands.clone(),
+            writes: executed.writes.clone(),
+            relative_base: executed.relative_base,
+            input: executed.input.clone(),
+            defaulted: executed.defaulted,
+            output: executed.output.clone(),
+        }
+    }
+}
+
+// Writes a `TraceRecord` as a line of JSON for each instruction the computer runs.
+// Clones of the computer keep writing to the same place.
+#[derive(Clone)]
+pub struct Tracer {
+    writer: Arc<Mutex<dyn Write + Send>>,
+}
+
+impl Tracer {
+    pub fn new<T: Write + Send + 'static>(writer: T) -> Tracer {
+        Tracer {
+            writer: Arc::new(Mutex::new(writer)),
+        }
+    }
+
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Tracer> {
+        Ok(Tracer::new(BufWriter::new(File::create(path)?)))
+    }
+
+    pub fn record<W: Word>(&self, record: &TraceRecord<W>) -> io::Result<()> {
+        let mut writer = self.writer.lock().expect("tracer lock poisoned");
+        serde_json::to_writer(&mut *writer, record)?;
+        writer.write_all(b"\n")
+    }
+
+    pub fn flush(&self) -> io::Result<()> {
+        self.writer.lock().expect("tracer lock poisoned").flush()
+    }
+}
+
+impl fmt::Debug for Tracer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Tracer")
+    }
+}
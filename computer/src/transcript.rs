@@ -0,0 +1,113 @@
+use super::Word;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{read_to_string, write};
+use std::io;
+use std::path::Path;
+
+// `instruction` is the computer's `instruction_count` when the value was read or written
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Event<W = i64> {
+    Input { instruction: u64, value: W },
+    Output { instruction: u64, value: W },
+}
+
+impl<W> Event<W> {
+    pub fn instruction(&self) -> u64 {
+        match self {
+            Event::Input { instruction, .. } | Event::Output { instruction, .. } => *instruction,
+        }
+    }
+}
+
+impl<W: Word> fmt::Display for Event<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Event::Input { instruction, value } => {
+                write!(f, "input {} at instruction {}", value, instruction)
+            }
+            Event::Output { instruction, value } => {
+                write!(f, "output {} at instruction {}", value, instruction)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Transcript<W = i64> {
+    pub events: Vec<Event<W>>,
+}
+
+impl<W: Word> Transcript<W> {
+    pub fn new() -> Transcript<W> {
+        Transcript { events: Vec::new() }
+    }
+
+    pub fn inputs(&self) -> impl Iterator<Item = &W> {
+        self.events.iter().filter_map(|event| match event {
+            Event::Input { value, .. } => Some(value),
+            Event::Output { .. } => None,
+        })
+    }
+
+    pub fn outputs(&self) -> impl Iterator<Item = &W> {
+        self.events.iter().filter_map(|event| match event {
+            Event::Output { value, .. } => Some(value),
+            Event::Input { .. } => None,
+        })
+    }
+}
+
+impl<W: Word> Default for Transcript<W> {
+    fn default() -> Transcript<W> {
+        Transcript::new()
+    }
+}
+
+impl<W: Word> Transcript<W> {
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        write(path, json)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Transcript<W>> {
+        let json = read_to_string(path)?;
+        serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+// The events of a transcript still to be replayed
+#[derive(Debug, Clone)]
+pub struct Replay<W = i64> {
+    pending: VecDeque<Event<W>>,
+}
+
+impl<W: Word> Replay<W> {
+    pub fn new(transcript: Transcript<W>) -> Replay<W> {
+        Replay {
+            pending: transcript.events.into(),
+        }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<Event<W>> {
+        self.pending.pop_front()
+    }
+
+    // Undoing a step hands back the events it replayed
+    pub(crate) fn put_back(&mut self, event: Event<W>) {
+        self.pending.push_front(event);
+    }
+
+    pub(crate) fn peek(&self) -> Option<&Event<W>> {
+        self.pending.front()
+    }
+}
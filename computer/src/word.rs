@@ -0,0 +1,123 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+// The value stored in each memory cell. Fixed width words wrap on overflow,
+// use `i128` or the `bigint` feature when a program needs more range.
+pub trait Word:
+    Clone
+    + fmt::Debug
+    + fmt::Display
+    + PartialEq
+    + PartialOrd
+    + FromStr
+    + Serialize
+    + DeserializeOwned
+    + Send
+    + Sync
+    + 'static
+{
+    fn from_i64(value: i64) -> Self;
+    fn to_i64(&self) -> Option<i64>;
+    fn wrapping_add(&self, other: &Self) -> Self;
+    fn wrapping_mul(&self, other: &Self) -> Self;
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+
+    fn is_zero(&self) -> bool {
+        self.to_i64() == Some(0)
+    }
+}
+
+impl Word for i64 {
+    fn from_i64(value: i64) -> i64 {
+        value
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        Some(*self)
+    }
+
+    fn wrapping_add(&self, other: &i64) -> i64 {
+        i64::wrapping_add(*self, *other)
+    }
+
+    fn wrapping_mul(&self, other: &i64) -> i64 {
+        i64::wrapping_mul(*self, *other)
+    }
+
+    fn checked_add(&self, other: &i64) -> Option<i64> {
+        i64::checked_add(*self, *other)
+    }
+
+    fn checked_mul(&self, other: &i64) -> Option<i64> {
+        i64::checked_mul(*self, *other)
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0
+    }
+}
+
+impl Word for i128 {
+    fn from_i64(value: i64) -> i128 {
+        value as i128
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        i64::try_from(*self).ok()
+    }
+
+    fn wrapping_add(&self, other: &i128) -> i128 {
+        i128::wrapping_add(*self, *other)
+    }
+
+    fn wrapping_mul(&self, other: &i128) -> i128 {
+        i128::wrapping_mul(*self, *other)
+    }
+
+    fn checked_add(&self, other: &i128) -> Option<i128> {
+        i128::checked_add(*self, *other)
+    }
+
+    fn checked_mul(&self, other: &i128) -> Option<i128> {
+        i128::checked_mul(*self, *other)
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == 0
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl Word for num_bigint::BigInt {
+    fn from_i64(value: i64) -> num_bigint::BigInt {
+        num_bigint::BigInt::from(value)
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        i64::try_from(self).ok()
+    }
+
+    fn wrapping_add(&self, other: &num_bigint::BigInt) -> num_bigint::BigInt {
+        self + other
+    }
+
+    fn wrapping_mul(&self, other: &num_bigint::BigInt) -> num_bigint::BigInt {
+        self * other
+    }
+
+    fn checked_add(&self, other: &num_bigint::BigInt) -> Option<num_bigint::BigInt> {
+        Some(self + other)
+    }
+
+    fn checked_mul(&self, other: &num_bigint::BigInt) -> Option<num_bigint::BigInt> {
+        Some(self * other)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.sign() == num_bigint::Sign::NoSign
+    }
+}
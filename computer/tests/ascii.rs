@@ -0,0 +1,66 @@
+mod ascii {
+    use computer::{AsciiOutput, Computer, IOMode, IterIo, StopReason};
+
+    // Echoes characters until a newline, then outputs 1000
+    const ECHO_LINE: &str = "3,20,4,20,1008,20,10,21,1005,21,14,1105,1,0,104,1000,99,0,0,0,0,0";
+
+    fn chars(text: &str) -> Vec<i64> {
+        text.bytes().map(|b| b as i64).collect()
+    }
+
+    #[test]
+    fn send_line_and_read() {
+        let mut c = Computer::from_string(ECHO_LINE, IOMode::Buffer).unwrap();
+        c.send_line("hello");
+        assert_eq!(c.buffer_io().input.len(), 6);
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+
+        let output = c.read_ascii();
+        assert_eq!(output.text, "hello\n");
+        assert_eq!(output.value, Some(1000));
+        assert_eq!(output.lines().collect::<Vec<_>>(), ["hello"]);
+        assert!(c.buffer_io().output.is_empty());
+    }
+
+    #[test]
+    fn read_lines_leaves_value() {
+        let mut c = Computer::from_string(ECHO_LINE, IOMode::Buffer).unwrap();
+        c.send_line("hi there");
+        c.run().unwrap();
+        assert_eq!(c.read_lines(), ["hi there"]);
+        assert_eq!(c.output_from_buffer(), 1000);
+        assert!(c.read_lines().is_empty());
+    }
+
+    #[test]
+    fn try_helpers_without_buffer_io() {
+        let mut c = Computer::with_io(ECHO_LINE, IterIo::new(chars("hi\n"))).unwrap();
+        assert!(!c.try_send_line("hello"));
+        assert!(c.try_read_ascii().is_none());
+        assert!(c.try_read_lines().is_none());
+
+        let mut c = Computer::from_string(ECHO_LINE, IOMode::Buffer).unwrap();
+        assert!(c.try_send_line("hi"));
+        c.run().unwrap();
+        assert_eq!(c.try_read_lines().unwrap(), ["hi"]);
+        assert_eq!(c.try_read_ascii().unwrap().value, Some(1000));
+    }
+
+    #[test]
+    fn text_without_value() {
+        let output = AsciiOutput::from_values(chars("one\ntwo\nthree"));
+        assert_eq!(output.value, None);
+        assert_eq!(output.lines().collect::<Vec<_>>(), ["one", "two", "three"]);
+    }
+
+    #[test]
+    fn values_inside_text() {
+        let mut values = chars("a");
+        values.push(500);
+        values.extend(chars("b\n"));
+        values.push(-3);
+        let output = AsciiOutput::from_values(values);
+        assert_eq!(output.text, "a500b\n");
+        assert_eq!(output.value, Some(-3));
+    }
+}
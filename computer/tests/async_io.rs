@@ -0,0 +1,109 @@
+#[cfg(feature = "async")]
+mod async_io {
+    use computer::{Computer, ComputerError, IOMode, InputPolicy, StopReason, ASYNC_SLICE};
+    use futures::channel::mpsc::unbounded;
+    use futures::executor::block_on;
+    use futures::future::join_all;
+    use futures::stream::{self, StreamExt};
+    use futures::task::noop_waker;
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use std::time::Duration;
+
+    // Adds one to each input and outputs it, halting once the input reaches 500
+    const INCREMENT: &str = "3,20,1007,20,500,21,1006,21,18,1001,20,1,20,4,20,1105,1,0,99,0,0,0";
+
+    fn assert_send<T: Send>(_: &T) {}
+
+    #[test]
+    fn streams_in_and_out() {
+        let mut c = Computer::from_string(INCREMENT, IOMode::Buffer).unwrap();
+        let (tx, rx) = unbounded();
+        let reason = block_on(c.run_async(stream::iter(vec![1, 5, 9]), tx)).unwrap();
+        assert_eq!(reason, StopReason::NeedsInput);
+        assert_eq!(block_on(rx.collect::<Vec<i64>>()), [2, 6, 10]);
+
+        c.input_to_buffer(500);
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+    }
+
+    #[test]
+    fn ring_network_on_one_thread() {
+        let nodes = 50;
+        let (senders, receivers): (Vec<_>, Vec<_>) = (0..nodes).map(|_| unbounded()).unzip();
+        senders[0].unbounded_send(0).unwrap();
+
+        let machines = receivers.into_iter().enumerate().map(|(i, rx)| {
+            let tx = senders[(i + 1) % nodes].clone();
+            async move {
+                let mut c = Computer::from_string(INCREMENT, IOMode::Buffer).unwrap();
+                let reason = c.run_async(rx, tx).await.unwrap();
+                (reason, c.mem.memory[20])
+            }
+        });
+        let machines: Vec<_> = machines.collect();
+        assert_send(&machines[0]);
+        drop(senders);
+
+        let results = block_on(join_all(machines));
+        assert_eq!(results[0], (StopReason::Halted, 500));
+        for (i, (reason, last)) in results.iter().enumerate().skip(1) {
+            assert_eq!(*reason, StopReason::NeedsInput);
+            assert_eq!(*last, 451 + i as i64);
+        }
+    }
+
+    #[test]
+    fn yields_while_busy() {
+        let mut c = Computer::from_string("1105,1,0", IOMode::Buffer).unwrap();
+        let (tx, _rx) = unbounded();
+        let mut run = Box::pin(c.run_async(stream::empty(), tx));
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        for _ in 0..3 {
+            assert!(matches!(Pin::new(&mut run).poll(&mut cx), Poll::Pending));
+        }
+        drop(run);
+        assert_eq!(c.instruction_count, 3 * (ASYNC_SLICE - 1));
+        assert!(c.try_buffer_io().is_some());
+    }
+
+    #[test]
+    fn time_limit_covers_the_whole_run() {
+        let mut c = Computer::from_string("1105,1,0", IOMode::Buffer).unwrap();
+        c.time_limit = Some(Duration::from_millis(20));
+        let (tx, _rx) = unbounded();
+        let reason = block_on(c.run_async(stream::empty(), tx)).unwrap();
+        assert!(matches!(reason, StopReason::TimeLimit(_)));
+    }
+
+    #[test]
+    fn default_waits_for_ready_input() {
+        let mut c = Computer::from_string(INCREMENT, IOMode::Buffer).unwrap();
+        c.input_policy = InputPolicy::Default(-1);
+        c.instruction_limit = Some(20);
+        let (tx, rx) = unbounded();
+        let reason = block_on(c.run_async(stream::iter(vec![5]), tx)).unwrap();
+        assert_eq!(reason, StopReason::InstructionLimit(20));
+        assert_eq!(block_on(rx.take(2).collect::<Vec<i64>>()), [6, 0]);
+    }
+
+    #[test]
+    fn closed_sink_disconnects() {
+        let mut c = Computer::from_string(INCREMENT, IOMode::Buffer).unwrap();
+        let (tx, rx) = unbounded();
+        drop(rx);
+        let err = block_on(c.run_async(stream::iter(vec![1, 2]), tx)).unwrap_err();
+        assert_eq!(err, ComputerError::Disconnected { ip: 13, opcode: 4 });
+    }
+
+    #[test]
+    fn own_io_is_restored() {
+        let mut c = Computer::from_string(INCREMENT, IOMode::Buffer).unwrap();
+        c.input_to_buffer(7);
+        let (tx, _rx) = unbounded();
+        block_on(c.run_async(stream::empty(), tx)).unwrap();
+        assert_eq!(c.buffer_io().input, [7]);
+    }
+}
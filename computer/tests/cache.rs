@@ -0,0 +1,74 @@
+mod cache {
+    use computer::{Computer, IOMode};
+
+    // Outputs the immediate at address 1, then overwrites it with 6 and loops back once.
+    const PATCH_OPERAND: &str = "104,5,1008,1,5,20,1006,20,17,1101,0,6,1,1105,1,0,99,99";
+
+    // Adds 2 + 3 into address 30 and outputs it, then patches the opcode at address 0
+    // from add to mul and loops back once.
+    const PATCH_OPCODE: &str =
+        "1101,2,3,30,4,30,1008,0,1101,31,1006,31,20,1101,0,1102,0,1105,1,0,99";
+
+    fn outputs(program: &str, decode_cache: bool) -> Vec<i64> {
+        let mut c = Computer::from_string(program, IOMode::Buffer).unwrap();
+        c.decode_cache = decode_cache;
+        c.instruction_limit = Some(100);
+        c.run().unwrap();
+        c.buffer_io().output.iter().copied().collect()
+    }
+
+    #[test]
+    fn self_modifying_operand() {
+        assert_eq!(outputs(PATCH_OPERAND, false), [5, 6]);
+        assert_eq!(outputs(PATCH_OPERAND, true), [5, 6]);
+    }
+
+    #[test]
+    fn self_modifying_opcode() {
+        assert_eq!(outputs(PATCH_OPCODE, false), [5, 6]);
+        assert_eq!(outputs(PATCH_OPCODE, true), [5, 6]);
+    }
+
+    #[test]
+    fn instruction_writing_over_itself() {
+        // Adds 7 to its own first parameter each time around the loop
+        let mut c = Computer::from_string("1101,0,7,1,4,1,1105,1,0", IOMode::Buffer).unwrap();
+        c.instruction_limit = Some(9);
+        c.run().unwrap();
+        assert_eq!(c.buffer_io().output, [7, 14, 21]);
+    }
+
+    #[test]
+    fn clear_decode_cache_after_external_write() {
+        let mut c = Computer::from_string("104,1,1105,1,0", IOMode::Buffer).unwrap();
+        c.instruction_limit = Some(2);
+        c.run().unwrap();
+        c.mem.memory[1] = 2;
+        c.clear_decode_cache();
+        c.instruction_limit = Some(3);
+        c.run().unwrap();
+        assert_eq!(c.buffer_io().output, [1, 2]);
+    }
+
+    #[test]
+    fn far_jump() {
+        let mut c =
+            Computer::from_string("1101,99,0,200000000,1105,1,200000000", IOMode::Buffer).unwrap();
+        c.run().unwrap();
+        assert!(c.is_completed());
+        assert_eq!(c.mem.instruction_pointer, 200_000_000);
+    }
+
+    #[test]
+    fn instruction_across_pages() {
+        // Outputs the immediate at 1024, on the page after its opcode, then rewrites it
+        let mut program = vec![0; 1032];
+        program[..3].copy_from_slice(&[1105, 1, 1023]);
+        program[1023..].copy_from_slice(&[104, 5, 1101, 0, 7, 1024, 1105, 1, 1023]);
+        let program: Vec<String> = program.iter().map(|v| v.to_string()).collect();
+        let mut c = Computer::from_string(&program.join(","), IOMode::Buffer).unwrap();
+        c.instruction_limit = Some(5);
+        c.run().unwrap();
+        assert_eq!(c.buffer_io().output, [5, 7]);
+    }
+}
@@ -1,51 +1,50 @@
 mod computer {
-    use computer::{Computer, IOMode};
+    use computer::{ChannelIo, Computer, IOMode, StopReason};
+    use std::time::Duration;
 
     fn run_input(input: &str) -> Computer {
-        let mut c = Computer::from_string(input, IOMode::Stdio);
-        c.run();
+        let mut c = Computer::from_string(input, IOMode::Stdio).unwrap();
+        c.run().unwrap();
         c
     }
 
     #[test]
     fn input_channel() {
-        let mut c = Computer::from_string("3,3,99,0", IOMode::Channel);
-        c.input_channel.send(12);
-        c.run();
-        assert_eq!(c.mem.memory[0..4], [3, 3, 99, 12]);
+        let mut c = Computer::from_string("3,3,99,0", IOMode::Channel).unwrap();
+        c.io_as::<ChannelIo>().unwrap().input.send(12).unwrap();
+        c.run().unwrap();
+        assert_eq!(c.mem.memory.read_range(0..4), [3, 3, 99, 12]);
     }
 
     #[test]
     fn output_channel() {
-        let mut c = Computer::from_string("4,3,99,10", IOMode::Channel);
-        c.run();
-        let res = c.output_channel.recv();
-        assert_eq!(res, 10);
+        let mut c = Computer::from_string("4,3,99,10", IOMode::Channel).unwrap();
+        c.run().unwrap();
+        let res = c.io_as::<ChannelIo>().unwrap().output.recv();
+        assert_eq!(res, Some(10));
     }
     #[test]
     fn input_buffer() {
-        let mut c = Computer::from_string("3,3,99,0", IOMode::Buffer);
-        c.mem.input_buffer.push_back(12);
-        c.run();
-        assert_eq!(c.mem.memory[0..4], [3, 3, 99, 12]);
+        let mut c = Computer::from_string("3,3,99,0", IOMode::Buffer).unwrap();
+        c.input_to_buffer(12);
+        c.run().unwrap();
+        assert_eq!(c.mem.memory.read_range(0..4), [3, 3, 99, 12]);
     }
 
     #[test]
     fn input_buffer_rel_1() {
-        let mut c = Computer::from_string("203,3,99,0", IOMode::Buffer);
-        c.mem.input_buffer.push_back(12);
-        c.enable_logger = true;
-        c.run();
-        assert_eq!(c.mem.memory[0..4], [203, 3, 99, 12]);
+        let mut c = Computer::from_string("203,3,99,0", IOMode::Buffer).unwrap();
+        c.input_to_buffer(12);
+        c.run().unwrap();
+        assert_eq!(c.mem.memory.read_range(0..4), [203, 3, 99, 12]);
     }
 
     #[test]
     fn input_buffer_rel_2() {
-        let mut c = Computer::from_string("109,2,203,0,99", IOMode::Buffer);
-        c.mem.input_buffer.push_back(12);
-        c.enable_logger = true;
-        c.run();
-        assert_eq!(c.mem.memory[0..5], [109, 2, 12, 0, 99]);
+        let mut c = Computer::from_string("109,2,203,0,99", IOMode::Buffer).unwrap();
+        c.input_to_buffer(12);
+        c.run().unwrap();
+        assert_eq!(c.mem.memory.read_range(0..5), [109, 2, 12, 0, 99]);
     }
 
     #[test]
@@ -62,40 +61,40 @@ mod computer {
 
     #[test]
     fn output_buffer() {
-        let mut c = Computer::from_string("4,3,99,12", IOMode::Buffer);
-        c.run();
-        let output = c.mem.output_buffer.pop_front().unwrap();
+        let mut c = Computer::from_string("4,3,99,12", IOMode::Buffer).unwrap();
+        c.run().unwrap();
+        let output = c.output_from_buffer();
         assert_eq!(output, 12);
     }
 
     #[test]
     fn add_pos() {
         let c = run_input("1,0,0,0,99");
-        assert_eq!(c.mem.memory[0..5], [2, 0, 0, 0, 99])
+        assert_eq!(c.mem.memory.read_range(0..5), [2, 0, 0, 0, 99])
     }
 
     #[test]
     fn mul_pos() {
         let c = run_input("2,3,0,3,99");
-        assert_eq!(c.mem.memory[0..5], [2, 3, 0, 6, 99])
+        assert_eq!(c.mem.memory.read_range(0..5), [2, 3, 0, 6, 99])
     }
 
     #[test]
     fn day2_example_4() {
         let c = run_input("1,1,1,4,99,5,6,0,99");
-        assert_eq!(c.mem.memory[0..9], [30, 1, 1, 4, 2, 5, 6, 0, 99])
+        assert_eq!(c.mem.memory.read_range(0..9), [30, 1, 1, 4, 2, 5, 6, 0, 99])
     }
 
     #[test]
     fn add_imm() {
         let c = run_input("1101,2,3,0,99");
-        assert_eq!(c.mem.memory[0..5], [5, 2, 3, 0, 99])
+        assert_eq!(c.mem.memory.read_range(0..5), [5, 2, 3, 0, 99])
     }
 
     #[test]
     fn mul_imm() {
         let c = run_input("1102,2,3,0,99");
-        assert_eq!(c.mem.memory[0..5], [6, 2, 3, 0, 99])
+        assert_eq!(c.mem.memory.read_range(0..5), [6, 2, 3, 0, 99])
     }
 
     #[test]
@@ -139,36 +138,141 @@ mod computer {
     #[test]
     fn lessthan_pos() {
         let mut c = run_input("7,5,6,7,99,3,4,0");
-        assert_eq!(c.mem.memory[0..8], [7, 5, 6, 7, 99, 3, 4, 1]);
+        assert_eq!(c.mem.memory.read_range(0..8), [7, 5, 6, 7, 99, 3, 4, 1]);
 
         c = run_input("7,5,6,7,99,4,3,0");
-        assert_eq!(c.mem.memory[0..8], [7, 5, 6, 7, 99, 4, 3, 0]);
+        assert_eq!(c.mem.memory.read_range(0..8), [7, 5, 6, 7, 99, 4, 3, 0]);
     }
 
     #[test]
     fn lessthan_imm() {
         let mut c = run_input("1107,3,4,5,99,0");
-        assert_eq!(c.mem.memory[0..6], [1107, 3, 4, 5, 99, 1]);
+        assert_eq!(c.mem.memory.read_range(0..6), [1107, 3, 4, 5, 99, 1]);
 
         c = run_input("1107,4,3,5,99,0");
-        assert_eq!(c.mem.memory[0..6], [1107, 4, 3, 5, 99, 0]);
+        assert_eq!(c.mem.memory.read_range(0..6), [1107, 4, 3, 5, 99, 0]);
     }
 
     #[test]
     fn eq_pos() {
         let mut c = run_input("8,5,6,7,99,4,4,0");
-        assert_eq!(c.mem.memory[0..8], [8, 5, 6, 7, 99, 4, 4, 1]);
+        assert_eq!(c.mem.memory.read_range(0..8), [8, 5, 6, 7, 99, 4, 4, 1]);
 
         c = run_input("8,5,6,7,99,4,3,0");
-        assert_eq!(c.mem.memory[0..8], [8, 5, 6, 7, 99, 4, 3, 0]);
+        assert_eq!(c.mem.memory.read_range(0..8), [8, 5, 6, 7, 99, 4, 3, 0]);
     }
 
     #[test]
     fn eq_imm() {
         let mut c = run_input("1108,4,4,5,99,0");
-        assert_eq!(c.mem.memory[0..6], [1108, 4, 4, 5, 99, 1]);
+        assert_eq!(c.mem.memory.read_range(0..6), [1108, 4, 4, 5, 99, 1]);
 
         c = run_input("1108,4,5,5,99,0");
-        assert_eq!(c.mem.memory[0..6], [1108, 4, 5, 5, 99, 0]);
+        assert_eq!(c.mem.memory.read_range(0..6), [1108, 4, 5, 5, 99, 0]);
+    }
+
+    #[test]
+    fn input_buffer_empty_needs_input() {
+        let mut c = Computer::from_string("3,5,4,5,99,0", IOMode::Buffer).unwrap();
+        assert_eq!(c.run().unwrap(), StopReason::NeedsInput);
+        assert_eq!(c.mem.instruction_pointer, 0);
+
+        c.input_to_buffer(7);
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+        assert_eq!(c.output_from_buffer(), 7);
+        assert!(!c.is_running());
+    }
+
+    #[test]
+    fn break_on_output() {
+        let mut c = Computer::from_string("104,1,104,2,99", IOMode::Buffer).unwrap();
+        c.break_on_output = true;
+        assert_eq!(c.run().unwrap(), StopReason::Output(1));
+        assert!(c.is_running());
+        assert_eq!(c.run().unwrap(), StopReason::Output(2));
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+        assert_eq!(c.buffer_io().output, [1, 2]);
+    }
+
+    #[test]
+    fn breakpoint_and_resume() {
+        let mut c = Computer::from_string("1101,1,1,9,1101,2,2,10,99,0,0", IOMode::Buffer).unwrap();
+        c.breakpoints.insert(4);
+        assert_eq!(c.run().unwrap(), StopReason::Breakpoint(4));
+        assert_eq!(c.run().unwrap(), StopReason::Breakpoint(4));
+        assert_eq!(c.mem.memory.read_range(9..11), [2, 0]);
+
+        assert_eq!(c.resume().unwrap(), StopReason::Halted);
+        assert_eq!(c.mem.memory.read_range(9..11), [2, 4]);
+    }
+
+    #[test]
+    fn run_until_condition() {
+        let mut c = Computer::from_string("1101,1,1,9,1101,2,2,10,99,0,0", IOMode::Buffer).unwrap();
+        let reason = c.run_until(|c| c.mem.memory[9] == 2).unwrap();
+        assert_eq!(reason, StopReason::Breakpoint(4));
+    }
+
+    #[test]
+    fn day7_feedback_loop() {
+        let program =
+            "3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5";
+        let mut amps: Vec<Computer> = [9, 8, 7, 6, 5]
+            .iter()
+            .map(|&phase| {
+                let mut c = Computer::from_string(program, IOMode::Buffer).unwrap();
+                c.break_on_output = true;
+                c.input_to_buffer(phase);
+                c
+            })
+            .collect();
+
+        let mut signal = 0;
+        while amps[4].is_running() {
+            for amp in amps.iter_mut() {
+                amp.input_to_buffer(signal);
+                if let StopReason::Output(val) = amp.run().unwrap() {
+                    signal = val;
+                }
+            }
+        }
+        assert_eq!(signal, 139629729);
+    }
+
+    #[test]
+    fn instruction_count() {
+        let c = run_input("1101,1,1,0,1101,2,2,0,99");
+        assert_eq!(c.instruction_count, 2);
+    }
+
+    #[test]
+    fn instruction_limit() {
+        let mut c = Computer::from_string("1105,1,0", IOMode::Buffer).unwrap();
+        c.instruction_limit = Some(1000);
+        assert_eq!(c.run().unwrap(), StopReason::InstructionLimit(1000));
+        assert_eq!(c.run().unwrap(), StopReason::InstructionLimit(1000));
+
+        c.instruction_limit = Some(1500);
+        assert_eq!(c.run().unwrap(), StopReason::InstructionLimit(1500));
+    }
+
+    #[test]
+    fn instruction_limit_not_reached() {
+        let mut c = Computer::from_string("1101,1,1,0,99", IOMode::Buffer).unwrap();
+        c.instruction_limit = Some(1);
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+    }
+
+    #[test]
+    fn time_limit() {
+        let mut c = Computer::from_string("1105,1,0", IOMode::Buffer).unwrap();
+        c.time_limit = Some(Duration::from_millis(20));
+        match c.run().unwrap() {
+            StopReason::TimeLimit(count) => {
+                assert!(count > 0);
+                assert_eq!(count, c.instruction_count);
+            }
+            reason => panic!("unexpected stop reason {:?}", reason),
+        }
     }
 }
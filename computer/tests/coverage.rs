@@ -0,0 +1,108 @@
+mod coverage {
+    use computer::{Computer, Coverage, IOMode, Mark};
+
+    // Outputs 0 when the input is 0, otherwise outputs the input
+    const BRANCH: &str = "3,12,1005,12,9,104,0,99,0,4,12,99,0,0";
+
+    fn covered(program: &str, inputs: &[i64]) -> Computer {
+        let mut c = Computer::from_string(program, IOMode::Buffer).unwrap();
+        c.coverage = Some(Coverage::new());
+        for i in inputs {
+            c.input_to_buffer(*i);
+        }
+        c.run().unwrap();
+        c
+    }
+
+    #[test]
+    fn marks_one_run() {
+        let c = covered(BRANCH, &[0]);
+        let coverage = c.coverage.as_ref().unwrap();
+        assert_eq!(coverage.instructions().collect::<Vec<_>>(), [0, 2, 5, 7]);
+        assert_eq!(coverage.mark(3), Mark::Executed);
+        assert_eq!(coverage.mark(8), Mark::Untouched);
+        assert_eq!(coverage.mark(9), Mark::Untouched);
+        assert_eq!(coverage.mark(12), Mark::Data);
+        assert!(coverage.is_read(12) && coverage.is_written(12));
+        assert_eq!(coverage.summary(14), (8, 1, 5));
+    }
+
+    #[test]
+    fn merged_listing() {
+        let mut merged = Coverage::new();
+        for input in &[0, 5] {
+            merged.merge(covered(BRANCH, &[*input]).coverage.as_ref().unwrap());
+        }
+
+        let mut c = Computer::from_string(BRANCH, IOMode::Buffer).unwrap();
+        c.coverage = Some(merged);
+        assert_eq!(
+            c.coverage_listing().unwrap(),
+            "11 executed, 1 data, 2 untouched of 14 words
+     0 X 3,12
+     2 X 1005,12,9
+     5 X 104,0
+     7 X 99
+     8 . 0
+     9 X 4,12
+    11 X 99
+    12 D 0
+    13 . 0
+"
+        );
+    }
+
+    #[test]
+    fn listing_covers_data_past_the_program() {
+        let c = covered("1101,1,1,7,99", &[]);
+        let coverage = c.coverage.as_ref().unwrap();
+        assert!(!coverage.is_read(1));
+        let listing = coverage.listing(&c.mem.memory);
+        assert!(listing.starts_with("5 executed, 1 data, 2 untouched of 8 words\n"));
+        assert!(listing.contains("\n     5 . 0\n"));
+        assert!(listing.ends_with("\n     7 D 2\n"));
+    }
+
+    #[test]
+    fn listing_collapses_far_gaps() {
+        let c = covered("109,1000000000000,21101,1,1,0,99", &[]);
+        assert_eq!(
+            c.coverage_listing().unwrap(),
+            "7 executed, 1 data, 999999999993 untouched of 1000000000001 words
+     0 X 109,1000000000000
+     2 X 21101,1,1,0
+     6 X 99
+     7 . 0 (999999999993 words)
+1000000000000 D 0
+"
+        );
+    }
+
+    #[test]
+    fn relative_reads() {
+        let c = covered("109,6,204,1,99,0,0,42", &[]);
+        let coverage = c.coverage.as_ref().unwrap();
+        assert_eq!(coverage.mark(7), Mark::Data);
+        assert!(!coverage.is_written(7));
+        assert_eq!(coverage.mark(5), Mark::Untouched);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let c = covered(BRANCH, &[5]);
+        let coverage = c.coverage.unwrap();
+        let path = std::env::temp_dir().join(format!("coverage-{}.json", std::process::id()));
+        coverage.save(&path).unwrap();
+        let loaded = Coverage::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, coverage);
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let mut c = Computer::from_string(BRANCH, IOMode::Buffer).unwrap();
+        c.input_to_buffer(0);
+        c.run().unwrap();
+        assert!(c.coverage_listing().is_none());
+    }
+}
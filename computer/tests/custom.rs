@@ -0,0 +1,157 @@
+mod custom {
+    use computer::{Computer, ComputerError, History, IOMode, MemoryWrite, OpCode, StopReason};
+
+    // 10: swap the values at two addresses
+    fn register_swap(c: &mut Computer) {
+        c.register_opcode(10, 2, |i| {
+            let a = i.operation().data[1] as usize;
+            let b = i.operation().data[2] as usize;
+            let (x, y) = (i.read(a)?, i.read(b)?);
+            i.write(a, y)?;
+            i.write(b, x)?;
+            Ok(None)
+        });
+    }
+
+    #[test]
+    fn custom_opcode() {
+        let mut c = Computer::from_string("10,6,7,4,6,99,7,8", IOMode::Buffer).unwrap();
+        register_swap(&mut c);
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+        assert_eq!(c.mem.memory.read_range(6..8), [8, 7]);
+        assert_eq!(c.output_from_buffer(), 8);
+        assert_eq!(c.instruction_count, 2);
+    }
+
+    #[test]
+    fn unregistered_opcode_errors() {
+        let mut c = Computer::from_string("10,5,6,99", IOMode::Buffer).unwrap();
+        assert_eq!(
+            c.run().unwrap_err(),
+            ComputerError::UnknownOpCode { ip: 0, opcode: 10 }
+        );
+    }
+
+    #[test]
+    fn modes_relative_base_and_io() {
+        // 42: output the sum of the relative base and an input, stored into the parameter
+        let mut c = Computer::from_string("109,100,242,5,4,105,99", IOMode::Buffer).unwrap();
+        c.register_opcode(42, 1, |i| {
+            let val = match i.input()? {
+                Some(val) => val,
+                None => return Ok(Some(StopReason::NeedsInput)),
+            };
+            let res = val + i.relative_base();
+            i.store(1, res)?;
+            i.output(res)?;
+            Ok(None)
+        });
+
+        assert_eq!(c.run().unwrap(), StopReason::NeedsInput);
+        assert_eq!(c.mem.instruction_pointer, 2);
+        c.input_to_buffer(5);
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+        assert_eq!(c.mem.memory[105], 105);
+        assert_eq!(c.buffer_io().output, [105, 105]);
+    }
+
+    #[test]
+    fn custom_jump() {
+        // 20: unconditional jump
+        let mut c = Computer::from_string("1120,4,104,1,104,2,99", IOMode::Buffer).unwrap();
+        c.register_opcode(20, 1, |i| {
+            let target = i.param(1)?;
+            i.jump(target)?;
+            Ok(None)
+        });
+        c.run().unwrap();
+        assert_eq!(c.buffer_io().output, [2]);
+    }
+
+    #[test]
+    fn built_ins_take_priority() {
+        let mut c = Computer::from_string("104,1,99", IOMode::Buffer).unwrap();
+        c.register_opcode(4, 1, |_| panic!("built-in opcode overridden"));
+        c.run().unwrap();
+        assert_eq!(c.output_from_buffer(), 1);
+    }
+
+    #[test]
+    fn step_decodes_custom_opcode() {
+        let mut c = Computer::from_string("10,3,4,7,8,99", IOMode::Buffer).unwrap();
+        register_swap(&mut c);
+        let step = c.step().unwrap();
+        assert_eq!(
+            step.operation.op_code,
+            OpCode::Custom {
+                code: 10,
+                parameters: 2
+            }
+        );
+        assert_eq!(c.mem.instruction_pointer, 3);
+        assert_eq!(c.mem.memory.read_range(3..5), [8, 7]);
+    }
+
+    #[test]
+    fn handler_errors_propagate() {
+        let mut c = Computer::from_string("111,0,99", IOMode::Buffer).unwrap();
+        c.register_opcode(11, 1, |i| i.store(1, 0).map(|_| None));
+        assert_eq!(
+            c.run().unwrap_err(),
+            ComputerError::ImmediateWrite { ip: 0, opcode: 111 }
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_many_parameters() {
+        let mut c = Computer::from_string("99", IOMode::Buffer).unwrap();
+        c.register_opcode(12, 4, |_| Ok(None));
+    }
+
+    #[test]
+    fn reregistering_decodes_again() {
+        let mut c = Computer::from_string("50,0,0,99", IOMode::Buffer).unwrap();
+        c.register_opcode(50, 1, |_| Ok(None));
+        c.step().unwrap();
+        assert_eq!(c.mem.instruction_pointer, 2);
+
+        c.mem.instruction_pointer = 0;
+        c.register_opcode(50, 2, |_| Ok(None));
+        c.step().unwrap();
+        assert_eq!(c.mem.instruction_pointer, 3);
+
+        c.mem.instruction_pointer = 0;
+        assert!(c.unregister_opcode(50));
+        assert_eq!(
+            c.step().unwrap_err(),
+            ComputerError::UnknownOpCode { ip: 0, opcode: 50 }
+        );
+    }
+
+    #[test]
+    fn writes_are_recorded_and_undone() {
+        let mut c = Computer::from_string("10,6,7,99,0,0,7,8", IOMode::Buffer).unwrap();
+        register_swap(&mut c);
+        c.history = Some(History::new(10));
+        let step = c.step().unwrap();
+        assert_eq!(
+            step.writes,
+            [
+                MemoryWrite {
+                    address: 6,
+                    old: 7,
+                    new: 8
+                },
+                MemoryWrite {
+                    address: 7,
+                    old: 8,
+                    new: 7
+                },
+            ]
+        );
+
+        c.step_back().unwrap();
+        assert_eq!(c.mem.memory.read_range(6..8), [7, 8]);
+    }
+}
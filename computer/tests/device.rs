@@ -0,0 +1,137 @@
+mod device {
+    use computer::{Computer, FnDevice, History, IOMode, MemoryDevice};
+    use std::sync::atomic::{AtomicI64, Ordering};
+    use std::sync::Arc;
+
+    #[derive(Debug, Clone, Default)]
+    struct Framebuffer {
+        pixels: Vec<i64>,
+    }
+
+    impl MemoryDevice for Framebuffer {
+        fn read(&mut self, offset: usize) -> i64 {
+            self.pixels.get(offset).copied().unwrap_or(0)
+        }
+
+        fn write(&mut self, offset: usize, value: i64) {
+            if offset >= self.pixels.len() {
+                self.pixels.resize(offset + 1, 0);
+            }
+            self.pixels[offset] = value;
+        }
+    }
+
+    // Writes 7 and 8 to the framebuffer at 1000, then outputs the first pixel
+    const DRAW: &str = "1101,3,4,1000,1101,4,4,1001,4,1000,99";
+
+    #[test]
+    fn framebuffer() {
+        let mut c = Computer::from_string(DRAW, IOMode::Buffer).unwrap();
+        c.mem.devices.attach(1000..1004, Framebuffer::default());
+        c.run().unwrap();
+
+        let fb = c.mem.devices.device_as::<Framebuffer>().unwrap();
+        assert_eq!(fb.pixels, [7, 8]);
+        assert_eq!(c.output_from_buffer(), 7);
+        assert_eq!(c.mem.memory.read_range(1000..1002), [0, 0]);
+        assert_eq!(c.mem.memory.allocated_pages(), 1);
+    }
+
+    #[test]
+    fn clock_cell() {
+        let ticks = Arc::new(AtomicI64::new(0));
+        let clock = ticks.clone();
+        let mut c = Computer::from_string("4,500,4,500,4,500,99", IOMode::Buffer).unwrap();
+        c.mem.devices.attach(
+            500..501,
+            FnDevice::new(
+                move |_| clock.fetch_add(1, Ordering::SeqCst),
+                |_, _: i64| {},
+            ),
+        );
+        c.run().unwrap();
+        assert_eq!(c.buffer_io().output, [0, 1, 2]);
+        assert_eq!(ticks.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn only_recorded_writes_read_the_device() {
+        let reads = Arc::new(AtomicI64::new(0));
+        let counter = reads.clone();
+        let mut c = Computer::from_string("1101,1,2,500,1101,1,2,500,99", IOMode::Buffer).unwrap();
+        c.mem.devices.attach(
+            500..501,
+            FnDevice::new(
+                move |_| counter.fetch_add(1, Ordering::SeqCst),
+                |_, _: i64| {},
+            ),
+        );
+        c.step().unwrap();
+        assert_eq!(reads.load(Ordering::SeqCst), 1);
+        c.run().unwrap();
+        assert_eq!(reads.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn step_records_device_write() {
+        let mut c = Computer::from_string(DRAW, IOMode::Buffer).unwrap();
+        c.mem.devices.attach(1000..1004, Framebuffer::default());
+        let step = c.step().unwrap();
+        assert_eq!(step.writes[0].address, 1000);
+        assert_eq!(step.writes[0].new, 7);
+    }
+
+    #[test]
+    fn step_back_restores_device() {
+        let mut c = Computer::from_string(DRAW, IOMode::Buffer).unwrap();
+        c.mem
+            .devices
+            .attach(1000..1004, Framebuffer { pixels: vec![5, 6] });
+        c.mem.memory.write(1000, 9);
+        c.history = Some(History::new(10));
+        let step = c.step().unwrap();
+        assert_eq!(step.writes[0].old, 5);
+
+        c.step_back().unwrap();
+        let fb = c.mem.devices.device_as::<Framebuffer>().unwrap();
+        assert_eq!(fb.pixels, [5, 6]);
+        assert_eq!(c.mem.memory.read(1000), 9);
+    }
+
+    #[test]
+    fn detach() {
+        let mut c = Computer::from_string(DRAW, IOMode::Buffer).unwrap();
+        c.mem.devices.attach(1000..1004, Framebuffer::default());
+        assert!(c.mem.devices.detach(1002).is_some());
+        assert!(c.mem.devices.is_empty());
+        c.run().unwrap();
+        assert_eq!(c.mem.memory.read_range(1000..1002), [7, 8]);
+    }
+
+    #[test]
+    fn clone_copies_devices() {
+        let mut c = Computer::from_string(DRAW, IOMode::Buffer).unwrap();
+        c.mem.devices.attach(1000..1004, Framebuffer::default());
+        let mut copy = c.clone();
+        copy.run().unwrap();
+        assert_eq!(
+            copy.mem.devices.device_as::<Framebuffer>().unwrap().pixels,
+            [7, 8]
+        );
+        assert!(c
+            .mem
+            .devices
+            .device_as::<Framebuffer>()
+            .unwrap()
+            .pixels
+            .is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn overlapping_devices() {
+        let mut c = Computer::from_string(DRAW, IOMode::Buffer).unwrap();
+        c.mem.devices.attach(1000..1004, Framebuffer::default());
+        c.mem.devices.attach(1003..1005, Framebuffer::default());
+    }
+}
@@ -0,0 +1,104 @@
+mod error {
+    use computer::{Computer, ComputerError, IOMode, Operation};
+
+    fn run_input(input: &str) -> Result<Computer, ComputerError> {
+        let mut c = Computer::from_string(input, IOMode::Buffer)?;
+        c.run()?;
+        Ok(c)
+    }
+
+    #[test]
+    fn invalid_integer() {
+        let err = Computer::from_string("1,2,x,4", IOMode::Buffer).unwrap_err();
+        assert_eq!(
+            err,
+            ComputerError::InvalidInteger {
+                position: 2,
+                value: "x".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn trailing_newline_is_accepted() {
+        let c = run_input("1101,2,3,0,99\n").unwrap();
+        assert_eq!(c.mem.memory[0], 5);
+    }
+
+    #[test]
+    fn unknown_opcode() {
+        let err = run_input("1101,40,2,4,0").unwrap_err();
+        assert_eq!(err, ComputerError::UnknownOpCode { ip: 4, opcode: 42 });
+
+        let c = Computer::from_string("42,0,0,0", IOMode::Buffer).unwrap();
+        let err = Operation::from_computer(&c).unwrap_err();
+        assert_eq!(err, ComputerError::UnknownOpCode { ip: 0, opcode: 42 });
+    }
+
+    #[test]
+    fn unknown_mode() {
+        let err = run_input("301,0,0,0,99").unwrap_err();
+        assert_eq!(
+            err,
+            ComputerError::UnknownMode {
+                ip: 0,
+                opcode: 301,
+                mode: 3
+            }
+        );
+    }
+
+    #[test]
+    fn immediate_write() {
+        let err = run_input("11101,1,1,0,99").unwrap_err();
+        assert_eq!(
+            err,
+            ComputerError::ImmediateWrite {
+                ip: 0,
+                opcode: 11101
+            }
+        );
+    }
+
+    #[test]
+    fn negative_address() {
+        let err = run_input("1,-1,0,0,99").unwrap_err();
+        assert_eq!(
+            err,
+            ComputerError::InvalidAddress {
+                ip: 0,
+                opcode: 1,
+                address: -1
+            }
+        );
+
+        let err = run_input("109,-5,22201,0,0,0,99").unwrap_err();
+        assert_eq!(
+            err,
+            ComputerError::InvalidAddress {
+                ip: 2,
+                opcode: 22201,
+                address: -5
+            }
+        );
+    }
+
+    #[test]
+    fn negative_jump() {
+        let err = run_input("1105,1,-2,99").unwrap_err();
+        assert_eq!(
+            err,
+            ComputerError::InvalidAddress {
+                ip: 0,
+                opcode: 1105,
+                address: -2
+            }
+        );
+    }
+
+    #[test]
+    fn display() {
+        let err: ComputerError = ComputerError::UnknownOpCode { ip: 4, opcode: 42 };
+        assert_eq!(err.to_string(), "unknown opcode 42 at ip 4");
+    }
+}
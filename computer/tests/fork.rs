@@ -0,0 +1,110 @@
+mod fork {
+    use computer::{Computer, History, IOMode, StopReason};
+
+    // Adds its input to a counter at 5000 and outputs the new total
+    const COUNTER: &str = "3,100,1,100,5000,5000,4,5000,99";
+
+    #[test]
+    fn reset_restores_loaded_image() {
+        let mut c = Computer::from_string("1101,2,3,0,109,7,99", IOMode::Buffer).unwrap();
+        c.run().unwrap();
+        assert_eq!(c.mem.memory[0], 5);
+        assert_eq!(c.mem.relative_base, 7);
+
+        c.reset();
+        assert_eq!(c.mem.memory[0], 1101);
+        assert_eq!(c.mem.instruction_pointer, 0);
+        assert_eq!(c.mem.relative_base, 0);
+        assert_eq!(c.instruction_count, 0);
+
+        c.run().unwrap();
+        assert_eq!(c.mem.memory[0], 5);
+    }
+
+    #[test]
+    fn reset_drops_grown_memory() {
+        let mut c = Computer::from_string(COUNTER, IOMode::Buffer).unwrap();
+        c.input_to_buffer(3);
+        c.run().unwrap();
+        assert_eq!(c.mem.memory.allocated_pages(), 2);
+
+        c.reset();
+        assert_eq!(c.mem.memory.allocated_pages(), 1);
+        assert_eq!(c.mem.memory[5000], 0);
+        c.input_to_buffer(4);
+        c.run().unwrap();
+        assert_eq!(c.buffer_io().output, [3, 4]);
+    }
+
+    #[test]
+    fn fork_shares_pages_until_written() {
+        let mut parent = Computer::from_string(COUNTER, IOMode::Buffer).unwrap();
+        parent.input_to_buffer(3);
+        parent.run().unwrap();
+
+        let mut child = parent.fork();
+        assert_eq!(child.mem.memory.unique_pages(), 0);
+
+        child.reset();
+        child.input_to_buffer(10);
+        assert_eq!(child.run().unwrap(), StopReason::Halted);
+        assert_eq!(child.output_from_buffer(), 3);
+        assert_eq!(child.output_from_buffer(), 10);
+        assert_eq!(child.mem.memory.unique_pages(), 2);
+
+        assert_eq!(parent.mem.memory[5000], 3);
+        assert_eq!(parent.mem.memory[100], 3);
+        assert_eq!(parent.buffer_io().output, [3]);
+    }
+
+    #[test]
+    fn forks_branch_independently() {
+        let mut c = Computer::from_string(COUNTER, IOMode::Buffer).unwrap();
+        assert_eq!(c.run().unwrap(), StopReason::NeedsInput);
+
+        let results: Vec<i64> = (0..100)
+            .map(|i| {
+                let mut branch = c.fork();
+                branch.input_to_buffer(i);
+                branch.run().unwrap();
+                branch.output_from_buffer()
+            })
+            .collect();
+        assert_eq!(results, (0..100).collect::<Vec<i64>>());
+        assert_eq!(c.mem.memory[5000], 0);
+    }
+
+    #[test]
+    fn fork_starts_with_empty_history() {
+        let mut c = Computer::from_string(COUNTER, IOMode::Buffer).unwrap();
+        c.history = Some(History::new(10));
+        c.input_to_buffer(3);
+        c.run().unwrap();
+
+        let mut fork = c.fork();
+        let history = fork.history.as_ref().unwrap();
+        assert!(history.is_empty());
+        assert_eq!(history.capacity(), 10);
+        assert!(fork.step_back().is_none());
+        assert_eq!(c.history.as_ref().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn forks_keep_their_own_decoded_code() {
+        // Outputs its own parameter, then reads a new one over it and loops
+        let mut c = Computer::from_string("104,0,3,1,1105,1,0", IOMode::Buffer).unwrap();
+        c.instruction_limit = Some(1);
+        c.run().unwrap();
+
+        let mut fork = c.fork();
+        fork.input_to_buffer(5);
+        fork.instruction_limit = Some(4);
+        fork.run().unwrap();
+        assert_eq!(fork.buffer_io().output, [0, 5]);
+
+        c.input_to_buffer(6);
+        c.instruction_limit = Some(4);
+        c.run().unwrap();
+        assert_eq!(c.buffer_io().output, [0, 6]);
+    }
+}
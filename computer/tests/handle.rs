@@ -0,0 +1,129 @@
+mod handle {
+    use computer::{Computer, ComputerError, IOMode, InputPolicy, Replay, StopReason, Transcript};
+    use std::time::Duration;
+
+    // Outputs double each input until it reads 0
+    const DOUBLE_UNTIL_ZERO: &str = "3,15,1005,15,6,99,1002,15,2,16,4,16,1105,1,0,0,0";
+
+    fn spawn(program: &str) -> computer::ComputerHandle {
+        Computer::from_string(program, IOMode::Buffer)
+            .unwrap()
+            .spawn()
+    }
+
+    #[test]
+    fn send_and_recv() {
+        let handle = spawn(DOUBLE_UNTIL_ZERO);
+        for i in 1..5 {
+            handle.send(i).unwrap();
+            assert_eq!(handle.recv(), Some(i * 2));
+        }
+        handle.send(0).unwrap();
+        assert_eq!(handle.recv(), None);
+
+        let finished = handle.join().unwrap();
+        assert_eq!(finished.reason, StopReason::Halted);
+        assert_eq!(finished.computer.mem.memory[16], 8);
+        assert!(finished.outputs.is_empty());
+    }
+
+    #[test]
+    fn join_keeps_unread_outputs() {
+        let handle = spawn("104,1,104,2,104,3,99");
+        let finished = handle.join().unwrap();
+        assert_eq!(finished.reason, StopReason::Halted);
+        assert_eq!(finished.outputs, [1, 2, 3]);
+    }
+
+    #[test]
+    fn join_while_waiting_for_input_halts() {
+        let handle = spawn(DOUBLE_UNTIL_ZERO);
+        handle.send(3).unwrap();
+        assert_eq!(handle.recv(), Some(6));
+
+        let finished = handle.join().unwrap();
+        assert_eq!(finished.reason, StopReason::Halted);
+        assert_eq!(finished.computer.mem.instruction_pointer, 0);
+        assert_eq!(finished.computer.instruction_count, 5);
+    }
+
+    #[test]
+    fn send_after_stop_returns_value() {
+        let handle = spawn("99");
+        assert_eq!(handle.recv(), None);
+        assert_eq!(handle.send(5), Err(5));
+        assert!(handle.join().is_ok());
+    }
+
+    #[test]
+    fn errors_are_returned() {
+        let handle = spawn("3,0,42");
+        handle.send(1).unwrap();
+        assert_eq!(
+            handle.join().unwrap_err(),
+            ComputerError::UnknownOpCode { ip: 2, opcode: 42 }
+        );
+    }
+
+    #[test]
+    fn own_io_is_restored() {
+        let mut c = Computer::from_string("99", IOMode::Buffer).unwrap();
+        c.input_to_buffer(7);
+        let mut finished = c.spawn().join().unwrap();
+        assert_eq!(finished.computer.buffer_io().input, [7]);
+    }
+
+    #[test]
+    fn runs_past_outputs() {
+        let mut c = Computer::from_string("104,1,104,2,99", IOMode::Buffer).unwrap();
+        c.break_on_output = true;
+        let finished = c.spawn().join().unwrap();
+        assert_eq!(finished.reason, StopReason::Halted);
+        assert_eq!(finished.outputs, [1, 2]);
+    }
+
+    #[test]
+    fn runs_past_breakpoints() {
+        let mut c = Computer::from_string("104,1,104,2,99", IOMode::Buffer).unwrap();
+        c.breakpoints.insert(0);
+        c.breakpoints.insert(2);
+        let finished = c.spawn().join().unwrap();
+        assert_eq!(finished.reason, StopReason::Halted);
+        assert_eq!(finished.outputs, [1, 2]);
+    }
+
+    #[test]
+    fn keeps_waiting_after_input_timeouts() {
+        let mut c = Computer::from_string(DOUBLE_UNTIL_ZERO, IOMode::Buffer).unwrap();
+        c.input_policy = InputPolicy::Timeout(Duration::from_millis(1));
+        let handle = c.spawn();
+        std::thread::sleep(Duration::from_millis(20));
+        handle.send(4).unwrap();
+        assert_eq!(handle.recv(), Some(8));
+
+        let finished = handle.join().unwrap();
+        assert_eq!(finished.reason, StopReason::Halted);
+    }
+
+    #[test]
+    fn time_limit_covers_the_whole_run() {
+        let mut c = Computer::from_string("104,1,1105,1,0", IOMode::Buffer).unwrap();
+        c.break_on_output = true;
+        c.time_limit = Some(Duration::from_millis(50));
+        let handle = c.spawn();
+        std::thread::sleep(Duration::from_millis(500));
+        assert!(handle.is_finished());
+        assert!(matches!(
+            handle.join().unwrap().reason,
+            StopReason::TimeLimit(_)
+        ));
+    }
+
+    #[test]
+    fn stops_when_replay_runs_out() {
+        let mut c = Computer::from_string(DOUBLE_UNTIL_ZERO, IOMode::Buffer).unwrap();
+        c.replay = Some(Replay::new(Transcript::new()));
+        let finished = c.spawn().join().unwrap();
+        assert_eq!(finished.reason, StopReason::NeedsInput);
+    }
+}
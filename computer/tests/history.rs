@@ -0,0 +1,142 @@
+mod history {
+    use computer::{Computer, History, IOMode, StopReason};
+
+    // Reads two numbers, outputs their sum and product, storing the sum over its own input
+    const SUM_PRODUCT: &str = "3,17,3,18,1,17,18,19,2,17,18,20,4,19,4,20,99,0,0,0,0";
+
+    fn recording(program: &str, capacity: usize) -> Computer {
+        let mut c = Computer::from_string(program, IOMode::Buffer).unwrap();
+        c.history = Some(History::new(capacity));
+        c
+    }
+
+    #[test]
+    fn step_back_undoes_writes() {
+        let mut c = recording(SUM_PRODUCT, 100);
+        c.input_to_buffer(3);
+        c.input_to_buffer(4);
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+        assert_eq!(c.instruction_count, 6);
+        assert_eq!(c.mem.memory.read_range(17..21), [3, 4, 7, 12]);
+
+        let step = c.step_back().unwrap();
+        assert_eq!(step.ip, 14);
+        assert_eq!(step.output, Some(12));
+        assert_eq!(c.mem.instruction_pointer, 14);
+        assert_eq!(c.buffer_io().output, [7]);
+
+        c.step_back().unwrap();
+        c.step_back().unwrap();
+        assert_eq!(c.mem.memory.read_range(17..21), [3, 4, 7, 0]);
+        assert_eq!(c.mem.instruction_pointer, 8);
+        assert_eq!(c.instruction_count, 3);
+    }
+
+    #[test]
+    fn step_back_returns_input() {
+        let mut c = recording(SUM_PRODUCT, 100);
+        c.input_to_buffer(3);
+        c.input_to_buffer(4);
+        c.run().unwrap();
+        assert!(c.rewind_to(0));
+        assert_eq!(c.mem.memory.read_range(17..21), [0, 0, 0, 0]);
+        assert_eq!(c.buffer_io().input, [3, 4]);
+        assert!(c.buffer_io().output.is_empty());
+
+        c.run().unwrap();
+        assert_eq!(c.buffer_io().output, [7, 12]);
+        assert!(c.step_back().is_some());
+    }
+
+    #[test]
+    fn run_back_to_breakpoint() {
+        let mut c = recording(SUM_PRODUCT, 100);
+        c.input_to_buffer(3);
+        c.input_to_buffer(4);
+        c.run().unwrap();
+
+        c.breakpoints.insert(4);
+        assert_eq!(c.run_back(), Some(4));
+        assert_eq!(c.mem.memory[19], 0);
+        assert_eq!(c.run_back(), None);
+        assert_eq!(c.mem.instruction_pointer, 0);
+    }
+
+    #[test]
+    fn run_back_until_write() {
+        let mut c = recording(SUM_PRODUCT, 100);
+        c.input_to_buffer(3);
+        c.input_to_buffer(4);
+        c.run().unwrap();
+
+        // Find who wrote 12 to address 20
+        assert_eq!(c.run_back_until(|c| c.mem.memory[20] != 12), Some(8));
+    }
+
+    #[test]
+    fn rewind_matches_limited_run() {
+        let program = "109,30,21101,1,0,0,21207,0,10,1,1206,1,20,21201,0,1,0,1105,1,6,99";
+        let mut full = recording(program, 1000);
+        full.run().unwrap();
+        assert_eq!(full.mem.memory[30], 10);
+
+        for n in 0..full.instruction_count {
+            let mut limited = Computer::from_string(program, IOMode::Buffer).unwrap();
+            limited.instruction_limit = Some(n);
+            limited.run().unwrap();
+
+            let mut rewound = full.clone();
+            assert!(rewound.rewind_to(n));
+            assert_eq!(
+                rewound.mem.instruction_pointer,
+                limited.mem.instruction_pointer
+            );
+            assert_eq!(rewound.mem.relative_base, limited.mem.relative_base);
+            assert_eq!(
+                rewound.mem.memory.read_range(0..35),
+                limited.mem.memory.read_range(0..35)
+            );
+        }
+    }
+
+    #[test]
+    fn bounded_history() {
+        let mut c = recording("1101,1,1,0,1105,1,0", 5);
+        c.instruction_limit = Some(20);
+        c.run().unwrap();
+        assert_eq!(c.history.as_ref().unwrap().len(), 5);
+
+        assert!(!c.rewind_to(14));
+        assert!(!c.rewind_to(21));
+        assert_eq!(c.instruction_count, 20);
+        assert!(c.rewind_to(15));
+        assert!(c.step_back().is_none());
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        let mut c = Computer::from_string(SUM_PRODUCT, IOMode::Buffer).unwrap();
+        c.input_to_buffer(3);
+        c.input_to_buffer(4);
+        c.run().unwrap();
+        assert!(c.step_back().is_none());
+        assert!(!c.rewind_to(0));
+    }
+
+    #[test]
+    fn records_custom_halt() {
+        // 30: store 1 at its parameter and halt
+        let mut c = recording("30,3,99,0", 10);
+        c.register_opcode(30, 1, |i| {
+            i.store(1, 1)?;
+            Ok(Some(StopReason::Halted))
+        });
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+        assert_eq!(c.instruction_count, 1);
+        assert_eq!(c.history.as_ref().unwrap().len(), 1);
+
+        c.step_back().unwrap();
+        assert_eq!(c.mem.memory[3], 0);
+        assert_eq!(c.instruction_count, 0);
+    }
+}
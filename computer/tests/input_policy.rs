@@ -0,0 +1,133 @@
+mod input_policy {
+    use computer::{ChannelIo, Computer, History, IOMode, InputPolicy, StopReason, TraceRecord};
+    use std::time::{Duration, Instant};
+
+    // Outputs each input, halting once it reads 0
+    const ECHO_UNTIL_ZERO: &str = "3,9,4,9,1005,9,0,99,0,0";
+
+    fn echo(policy: InputPolicy) -> Computer {
+        let mut c = Computer::from_string(ECHO_UNTIL_ZERO, IOMode::Buffer).unwrap();
+        c.input_policy = policy;
+        c
+    }
+
+    #[test]
+    fn blocks_by_default() {
+        let mut c = echo(InputPolicy::default());
+        assert_eq!(c.input_policy, InputPolicy::Block);
+        assert_eq!(c.run().unwrap(), StopReason::NeedsInput);
+        assert_eq!(c.run().unwrap(), StopReason::NeedsInput);
+        assert_eq!(c.idle_reads, 2);
+
+        c.input_to_buffer(0);
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+        assert_eq!(c.idle_reads, 0);
+    }
+
+    #[test]
+    fn default_when_empty() {
+        let mut c = echo(InputPolicy::Default(-1));
+        c.input_to_buffer(5);
+        c.instruction_limit = Some(12);
+        assert_eq!(c.run().unwrap(), StopReason::InstructionLimit(12));
+        assert_eq!(c.buffer_io().output, [5, -1, -1, -1]);
+        assert_eq!(c.idle_reads, 3);
+
+        c.input_to_buffer(0);
+        c.instruction_limit = None;
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+        assert_eq!(c.idle_reads, 0);
+    }
+
+    #[test]
+    fn default_does_not_block_on_channel() {
+        let io = ChannelIo::new();
+        let input = io.input.clone();
+        let mut c = Computer::with_io(ECHO_UNTIL_ZERO, io).unwrap();
+        c.input_policy = InputPolicy::Default(-1);
+        c.instruction_limit = Some(30);
+        assert_eq!(c.run().unwrap(), StopReason::InstructionLimit(30));
+        assert_eq!(c.idle_reads, 10);
+
+        input.send(0).unwrap();
+        c.instruction_limit = None;
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+    }
+
+    #[test]
+    fn timeout_stops_for_input() {
+        let io = ChannelIo::new();
+        let input = io.input.clone();
+        let mut c = Computer::with_io(ECHO_UNTIL_ZERO, io).unwrap();
+        c.input_policy = InputPolicy::Timeout(Duration::from_millis(20));
+
+        let start = Instant::now();
+        assert_eq!(c.run().unwrap(), StopReason::NeedsInput);
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert_eq!(c.mem.instruction_pointer, 0);
+        assert_eq!(c.idle_reads, 1);
+
+        input.send(0).unwrap();
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+        assert_eq!(c.idle_reads, 0);
+    }
+
+    #[test]
+    fn stepping_back_over_a_default() {
+        let mut c = echo(InputPolicy::Default(-1));
+        c.history = Some(History::new(10));
+        c.instruction_limit = Some(1);
+        c.run().unwrap();
+        assert_eq!(c.mem.memory[9], -1);
+
+        let step = c.step_back().unwrap();
+        assert_eq!(step.input, Some(-1));
+        assert!(step.defaulted);
+        assert_eq!(c.mem.memory[9], 0);
+        assert!(c.buffer_io().input.is_empty());
+    }
+
+    #[test]
+    fn defaults_are_traced() {
+        let mut c = echo(InputPolicy::Default(-1));
+        c.input_to_buffer(4);
+        let step = c.step().unwrap();
+        assert_eq!((step.input, step.defaulted), (Some(4), false));
+        c.step().unwrap();
+        c.step().unwrap();
+        let step = c.step().unwrap();
+        assert_eq!((step.input, step.defaulted), (Some(-1), true));
+        let record = TraceRecord::new(3, &step);
+        assert_eq!((record.input, record.defaulted), (Some(-1), true));
+    }
+
+    #[test]
+    fn idle_network() {
+        // The first machine passes its outputs on to the second, the network is idle
+        // once neither has read anything for a few rounds
+        let mut machines: Vec<_> = (0..2).map(|_| echo(InputPolicy::Default(-1))).collect();
+        machines[0].input_to_buffer(7);
+        let mut received = Vec::new();
+        let mut rounds = 0;
+        while machines.iter().any(|m| m.idle_reads < 3) {
+            for m in machines.iter_mut() {
+                m.instruction_limit = Some(m.instruction_count + 3);
+                m.run().unwrap();
+            }
+            let sent: Vec<_> = machines[0].buffer_io().output.drain(..).collect();
+            for value in sent.into_iter().filter(|v| *v != -1) {
+                machines[1].input_to_buffer(value);
+            }
+            received.extend(
+                machines[1]
+                    .buffer_io()
+                    .output
+                    .drain(..)
+                    .filter(|v| *v != -1),
+            );
+            rounds += 1;
+        }
+        assert_eq!(received, [7]);
+        assert_eq!(rounds, 5);
+    }
+}
@@ -0,0 +1,159 @@
+mod io {
+    use computer::{
+        BufferIo, ChannelIo, Computer, ComputerError, FnIo, IntcodeIo, IoError, IterIo, StdioIo,
+        StopReason,
+    };
+    use std::sync::{Arc, Mutex};
+
+    const DOUBLE_UNTIL_ZERO: &str = "3,15,1005,15,6,99,1002,15,2,16,4,16,1105,1,0,0,0";
+
+    #[test]
+    fn iter_io() {
+        let mut c = Computer::with_io(DOUBLE_UNTIL_ZERO, IterIo::new(vec![1, 2, 3, 0])).unwrap();
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+        assert_eq!(
+            c.io_as::<IterIo<std::vec::IntoIter<i64>>>().unwrap().output,
+            [2, 4, 6]
+        );
+    }
+
+    #[test]
+    fn iter_io_exhausted_needs_input() {
+        let mut c = Computer::with_io(DOUBLE_UNTIL_ZERO, IterIo::new(1..3)).unwrap();
+        assert_eq!(c.run().unwrap(), StopReason::NeedsInput);
+        assert_eq!(c.mem.instruction_pointer, 0);
+    }
+
+    #[test]
+    fn fn_io() {
+        let outputs = Arc::new(Mutex::new(Vec::new()));
+        let sink = outputs.clone();
+        let mut next = 0;
+        let io = FnIo::new(
+            move || {
+                next += 1;
+                Some(if next > 3 { 0 } else { next * 10 })
+            },
+            move |val| sink.lock().unwrap().push(val),
+        );
+        let mut c = Computer::with_io(DOUBLE_UNTIL_ZERO, io).unwrap();
+        c.run().unwrap();
+        assert_eq!(*outputs.lock().unwrap(), [20, 40, 60]);
+    }
+
+    #[derive(Debug, Clone, Default)]
+    struct Accumulator {
+        total: i64,
+        reads: usize,
+    }
+
+    impl IntcodeIo for Accumulator {
+        fn read(&mut self) -> Result<Option<i64>, IoError> {
+            self.reads += 1;
+            match self.reads {
+                1..=4 => Ok(Some(self.reads as i64)),
+                _ => Ok(Some(0)),
+            }
+        }
+
+        fn write(&mut self, value: i64) -> Result<(), IoError> {
+            self.total += value;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn custom_device() {
+        let mut c = Computer::with_io(DOUBLE_UNTIL_ZERO, Accumulator::default()).unwrap();
+        c.run().unwrap();
+        let device = c.io_as::<Accumulator>().unwrap();
+        assert_eq!(device.total, 20);
+        assert_eq!(device.reads, 5);
+    }
+
+    #[derive(Debug, Clone)]
+    struct Broken;
+
+    impl IntcodeIo for Broken {
+        fn read(&mut self) -> Result<Option<i64>, IoError> {
+            Err(IoError::InvalidInput("abc".to_string()))
+        }
+
+        fn write(&mut self, _: i64) -> Result<(), IoError> {
+            Err(IoError::Disconnected)
+        }
+    }
+
+    #[test]
+    fn device_errors() {
+        let mut c = Computer::with_io("3,0,99", Broken).unwrap();
+        assert_eq!(
+            c.run().unwrap_err(),
+            ComputerError::InvalidInput {
+                ip: 0,
+                opcode: 3,
+                value: "abc".to_string()
+            }
+        );
+
+        let mut c = Computer::with_io("104,1,99", Broken).unwrap();
+        assert_eq!(
+            c.run().unwrap_err(),
+            ComputerError::Disconnected { ip: 0, opcode: 104 }
+        );
+    }
+
+    #[test]
+    fn io_as_wrong_type() {
+        let mut c = Computer::with_io("99", BufferIo::default()).unwrap();
+        assert!(c.io_as::<ChannelIo>().is_none());
+        assert!(c.io_as::<BufferIo>().is_some());
+    }
+
+    #[test]
+    fn try_buffer_helpers() {
+        let mut c = Computer::with_io("3,0,4,0,99", BufferIo::default()).unwrap();
+        assert_eq!(c.try_output_from_buffer(), None);
+        assert_eq!(c.try_input_to_buffer(7), Ok(()));
+        c.run().unwrap();
+        assert_eq!(c.try_output_from_buffer(), Some(7));
+
+        let mut c = Computer::with_io("99", IterIo::new(vec![1])).unwrap();
+        assert!(c.try_buffer_io().is_none());
+        assert_eq!(c.try_input_to_buffer(7), Err(7));
+        assert_eq!(c.try_output_from_buffer(), None);
+    }
+
+    #[test]
+    fn clone_copies_buffers() {
+        let mut c = Computer::with_io("3,0,4,0,99", BufferIo::default()).unwrap();
+        let mut copy = c.clone();
+        c.input_to_buffer(1);
+        copy.input_to_buffer(2);
+        c.run().unwrap();
+        copy.run().unwrap();
+        assert_eq!(c.output_from_buffer(), 1);
+        assert_eq!(copy.output_from_buffer(), 2);
+    }
+
+    #[test]
+    fn stdio_splits_lines() {
+        let mut io = StdioIo::new();
+        io.interactive = false;
+        io.push_line("1, 2 3,\t4\n").unwrap();
+        io.push_line("0").unwrap();
+        let mut c = Computer::with_io(DOUBLE_UNTIL_ZERO, io).unwrap();
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+        assert_eq!(c.mem.memory[16], 8);
+    }
+
+    #[test]
+    fn stdio_rejects_non_numbers() {
+        let mut io = StdioIo::<i64>::new();
+        assert_eq!(
+            io.push_line("5,six"),
+            Err(IoError::InvalidInput("six".to_string()))
+        );
+        assert_eq!(io.read(), Ok(Some(5)));
+    }
+}
@@ -0,0 +1,131 @@
+mod logging {
+    use computer::{Computer, IOMode};
+    use std::collections::HashMap;
+    use std::fmt;
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Level, Metadata, Subscriber};
+
+    #[derive(Debug)]
+    struct Logged {
+        level: Level,
+        fields: HashMap<String, String>,
+    }
+
+    impl Logged {
+        fn field(&self, name: &str) -> &str {
+            &self.fields[name]
+        }
+    }
+
+    impl Visit for Logged {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.fields
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.fields
+                .insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    // Keeps every event at or above `level`
+    struct Collector {
+        level: Level,
+        events: Arc<Mutex<Vec<Logged>>>,
+    }
+
+    impl Subscriber for Collector {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            *metadata.level() <= self.level
+        }
+
+        fn new_span(&self, _: &Attributes) -> Id {
+            Id::from_u64(1)
+        }
+
+        fn record(&self, _: &Id, _: &Record) {}
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event) {
+            let mut logged = Logged {
+                level: *event.metadata().level(),
+                fields: HashMap::new(),
+            };
+            event.record(&mut logged);
+            self.events.lock().unwrap().push(logged);
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    fn collect<F: FnOnce()>(level: Level, f: F) -> Vec<Logged> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let collector = Collector {
+            level,
+            events: events.clone(),
+        };
+        tracing::subscriber::with_default(collector, f);
+        let events = std::mem::take(&mut *events.lock().unwrap());
+        events
+    }
+
+    fn run(program: &str, inputs: &[i64]) -> Computer {
+        let mut c = Computer::from_string(program, IOMode::Buffer).unwrap();
+        c.name = "Amp A".to_string();
+        for i in inputs {
+            c.input_to_buffer(*i);
+        }
+        let _ = c.run();
+        c
+    }
+
+    #[test]
+    fn instructions_and_writes() {
+        let events = collect(Level::TRACE, || {
+            run("1002,4,3,4,33", &[]);
+        });
+        let execute = &events[0];
+        assert_eq!(execute.level, Level::TRACE);
+        assert_eq!(execute.field("message"), "execute");
+        assert_eq!(execute.field("name"), "Amp A");
+        assert_eq!(execute.field("ip"), "0");
+        assert_eq!(execute.field("opcode"), "Mul");
+        assert_eq!(execute.field("operands"), "[4, 3, 4]");
+
+        let write = &events[1];
+        assert_eq!(write.field("message"), "write");
+        assert_eq!(write.field("address"), "4");
+        assert_eq!(write.field("value"), "99");
+    }
+
+    #[test]
+    fn io_and_stops_at_debug() {
+        let events = collect(Level::DEBUG, || {
+            run("3,0,4,0,99", &[42]);
+        });
+        let messages: Vec<_> = events.iter().map(|e| e.field("message")).collect();
+        assert_eq!(messages, ["input", "output", "stopped"]);
+        assert!(events.iter().all(|e| e.level == Level::DEBUG));
+        assert_eq!(events[1].field("value"), "42");
+        assert_eq!(events[2].field("reason"), "Halted");
+        assert_eq!(events[2].field("ip"), "4");
+    }
+
+    #[test]
+    fn errors_at_warn() {
+        let events = collect(Level::WARN, || {
+            run("3,0,4,0,42", &[7]);
+            run("3,0,4,0,99", &[7]);
+        });
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].level, Level::WARN);
+        assert_eq!(events[0].field("message"), "failed");
+        assert_eq!(events[0].field("error"), "unknown opcode 42 at ip 4");
+    }
+}
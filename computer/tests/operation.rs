@@ -3,9 +3,9 @@ mod operation {
 
     #[test]
     fn should_parse_opcode_with_missing_initial_zero() {
-        let c = Computer::from_string("1002,3,3,0", IOMode::Stdio);
+        let c = Computer::from_string("1002,3,3,0", IOMode::Stdio).unwrap();
 
-        let opcode = Operation::from_computer(&c);
+        let opcode = Operation::from_computer(&c).unwrap();
         assert_eq!(
             opcode,
             Operation {
@@ -18,13 +18,13 @@ mod operation {
 
     #[test]
     fn should_parse_opcode_1() {
-        let c = Computer::from_string("01001,100,1,100", IOMode::Stdio);
-        let opcode = Operation::from_computer(&c);
+        let c = Computer::from_string("01001,100,1,100", IOMode::Stdio).unwrap();
+        let opcode = Operation::from_computer(&c).unwrap();
         assert_eq!(
             opcode,
             Operation {
                 op_code: OpCode::Add,
-                data: vec![01001, 100, 1, 100],
+                data: vec![1001, 100, 1, 100],
                 modes: (Mode::Position, Mode::Immediate, Mode::Position)
             }
         );
@@ -32,8 +32,8 @@ mod operation {
 
     #[test]
     fn should_parse_opcode() {
-        let c = Computer::from_string("01002,3,3,0", IOMode::Stdio);
-        let opcode = Operation::from_computer(&c);
+        let c = Computer::from_string("01002,3,3,0", IOMode::Stdio).unwrap();
+        let opcode = Operation::from_computer(&c).unwrap();
         assert_eq!(
             opcode,
             Operation {
@@ -43,8 +43,8 @@ mod operation {
             }
         );
 
-        let c = Computer::from_string("01002,3,3,0", IOMode::Stdio);
-        let opcode = Operation::from_computer(&c);
+        let c = Computer::from_string("01002,3,3,0", IOMode::Stdio).unwrap();
+        let opcode = Operation::from_computer(&c).unwrap();
         assert_eq!(
             opcode,
             Operation {
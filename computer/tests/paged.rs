@@ -0,0 +1,47 @@
+mod paged {
+    use computer::{Computer, IOMode, PagedMemory};
+
+    #[test]
+    fn unmapped_reads_are_zero() {
+        let m: PagedMemory = PagedMemory::new();
+        assert_eq!(m.read(0), 0);
+        assert_eq!(m.read(1 << 40), 0);
+        assert_eq!(m[12345], 0);
+        assert_eq!(m.allocated_pages(), 0);
+        assert!(m.is_empty());
+    }
+
+    #[test]
+    fn writes_allocate_pages_on_demand() {
+        let mut m: PagedMemory = PagedMemory::from(vec![1, 2, 3]);
+        assert_eq!(m.allocated_pages(), 1);
+        assert_eq!(m.len(), 3);
+
+        m.write(1_000_000, 7);
+        assert_eq!(m.allocated_pages(), 2);
+        assert_eq!(m.len(), 1_000_001);
+        assert_eq!(m.read(1_000_000), 7);
+        assert_eq!(m.read(999_999), 0);
+
+        m[4] = 9;
+        assert_eq!(m.read_range(0..5), [1, 2, 3, 0, 9]);
+        assert_eq!(m.allocated_pages(), 2);
+    }
+
+    #[test]
+    fn computer_grows_memory() {
+        let mut c = Computer::from_string("1101,1,2,5000000,4,5000000,99", IOMode::Buffer).unwrap();
+        c.run().unwrap();
+        assert_eq!(c.mem.memory[5_000_000], 3);
+        assert_eq!(c.buffer_io().output, [3]);
+        assert_eq!(c.mem.memory.allocated_pages(), 2);
+    }
+
+    #[test]
+    fn computer_reads_unmapped_as_zero() {
+        let mut c = Computer::from_string("109,100000,204,5,99", IOMode::Buffer).unwrap();
+        c.run().unwrap();
+        assert_eq!(c.buffer_io().output, [0]);
+        assert_eq!(c.mem.memory.allocated_pages(), 1);
+    }
+}
@@ -0,0 +1,132 @@
+mod profiler {
+    use computer::{
+        Computer, History, IOMode, JumpCount, Loop, OpCode, ProfileReport, Profiler, StopReason,
+    };
+
+    // Counts [30] up to 10, the compare at 6 and the jumps at 10 and 17 form the loop
+    const COUNT_TO_TEN: &str = "109,30,21101,1,0,0,21207,0,10,1,1206,1,20,21201,0,1,0,1105,1,6,99";
+
+    fn profiled(program: &str) -> Computer {
+        let mut c = Computer::from_string(program, IOMode::Buffer).unwrap();
+        c.profiler = Some(Profiler::new());
+        c
+    }
+
+    fn profile(program: &str) -> Profiler {
+        let mut c = profiled(program);
+        c.run().unwrap();
+        c.profiler.take().unwrap()
+    }
+
+    #[test]
+    fn counts_addresses_and_opcodes() {
+        let p = profile(COUNT_TO_TEN);
+        assert_eq!(p.instructions(), 41);
+        assert_eq!(
+            [0, 2, 6, 10, 13, 17, 20].map(|a| p.count_at(a)),
+            [1, 1, 10, 10, 9, 9, 1]
+        );
+        assert_eq!(p.count_at(3), 0);
+        assert_eq!(p.count_of(OpCode::Add), 10);
+        assert_eq!(p.count_of(OpCode::Lessthan), 10);
+        assert_eq!(p.count_of(OpCode::End), 1);
+        assert_eq!(p.count_of(OpCode::Mul), 0);
+    }
+
+    #[test]
+    fn jumps_and_loops() {
+        let report = profile(COUNT_TO_TEN).report(3);
+        assert_eq!(
+            report.jumps,
+            [
+                JumpCount {
+                    address: 10,
+                    taken: 1,
+                    not_taken: 9
+                },
+                JumpCount {
+                    address: 17,
+                    taken: 9,
+                    not_taken: 0
+                },
+            ]
+        );
+        assert_eq!(
+            report.loops,
+            [Loop {
+                from: 17,
+                to: 6,
+                count: 9
+            }]
+        );
+    }
+
+    #[test]
+    fn jump_to_next_instruction_is_taken() {
+        let report = profile("1105,1,3,99").report(1);
+        assert_eq!(
+            report.jumps,
+            [JumpCount {
+                address: 0,
+                taken: 1,
+                not_taken: 0
+            }]
+        );
+    }
+
+    #[test]
+    fn report_orders_hot_spots() {
+        let report = profile(COUNT_TO_TEN).report(3);
+        assert_eq!(report.instructions, 41);
+        let top: Vec<_> = report.top_addresses.iter().map(|a| a.address).collect();
+        assert_eq!(top, [6, 10, 13]);
+        assert_eq!(report.opcodes[0].count, 10);
+        assert_eq!(report.opcodes.last().unwrap().count, 1);
+        assert_eq!(report.opcodes.len(), 6);
+    }
+
+    #[test]
+    fn text_and_json() {
+        let report = profile(COUNT_TO_TEN).report(2);
+        let text = report.to_string();
+        assert!(text.starts_with("41 instructions executed\n"));
+        assert!(text.contains("\n       6           10  24.39%\n"));
+        assert!(text.contains("\n  JumpIfTrue              9  21.95%\n"));
+        assert!(text.contains("\n      17 -> 6                 9\n"));
+
+        let parsed: ProfileReport = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(parsed, report);
+    }
+
+    #[test]
+    fn waiting_for_input_is_not_counted() {
+        let mut c = profiled("3,0,99");
+        assert_eq!(c.run().unwrap(), StopReason::NeedsInput);
+        assert_eq!(c.run().unwrap(), StopReason::NeedsInput);
+        assert_eq!(c.profiler.as_ref().unwrap().instructions(), 0);
+
+        c.input_to_buffer(1);
+        c.run().unwrap();
+        assert_eq!(c.profiler.as_ref().unwrap().instructions(), 2);
+    }
+
+    #[test]
+    fn same_counts_when_stepping() {
+        let mut c = profiled(COUNT_TO_TEN);
+        c.history = Some(History::new(10));
+        c.run().unwrap();
+        assert_eq!(c.profiler.unwrap(), profile(COUNT_TO_TEN));
+    }
+
+    #[test]
+    fn counts_carry_on_until_cleared() {
+        let mut c = profiled(COUNT_TO_TEN);
+        c.run().unwrap();
+        c.reset();
+        c.run().unwrap();
+        let p = c.profiler.as_mut().unwrap();
+        assert_eq!(p.instructions(), 82);
+        p.clear();
+        assert_eq!(p.report(10).top_addresses, []);
+    }
+}
@@ -0,0 +1,108 @@
+mod protection {
+    use computer::{Access, Computer, ComputerError, IOMode, Protection};
+
+    #[test]
+    fn read_only_write_faults() {
+        let mut c = Computer::from_string("1101,1,2,0,99", IOMode::Buffer).unwrap();
+        c.mem.protection.protect(0..5, Protection::ReadOnly);
+        let err = c.run().unwrap_err();
+        assert_eq!(
+            err,
+            ComputerError::ProtectionFault {
+                ip: 0,
+                opcode: 1101,
+                address: 0,
+                access: Access::Write,
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "protection fault, write of address 0 by opcode 1101 at ip 0"
+        );
+        assert_eq!(c.mem.memory[0], 1101);
+        assert_eq!(c.mem.instruction_pointer, 0);
+    }
+
+    #[test]
+    fn relative_write_faults() {
+        let mut c = Computer::from_string("109,10,21101,1,2,0,99", IOMode::Buffer).unwrap();
+        c.mem.protection.protect(8..12, Protection::ReadOnly);
+        assert_eq!(
+            c.run().unwrap_err(),
+            ComputerError::ProtectionFault {
+                ip: 2,
+                opcode: 21101,
+                address: 10,
+                access: Access::Write,
+            }
+        );
+    }
+
+    #[test]
+    fn write_only() {
+        let mut c = Computer::from_string("1101,1,2,7,4,7,99,0", IOMode::Buffer).unwrap();
+        c.mem.protection.protect(7..8, Protection::WriteOnly);
+        assert_eq!(
+            c.run().unwrap_err(),
+            ComputerError::ProtectionFault {
+                ip: 4,
+                opcode: 4,
+                address: 7,
+                access: Access::Read,
+            }
+        );
+        assert_eq!(c.mem.memory[7], 3);
+    }
+
+    #[test]
+    fn no_execute_jump_target() {
+        let mut c = Computer::from_string("1105,1,5,0,0,104,7,99", IOMode::Buffer).unwrap();
+        c.mem.protection.protect(5..7, Protection::NoExecute);
+        assert_eq!(
+            c.run().unwrap_err(),
+            ComputerError::ProtectionFault {
+                ip: 5,
+                opcode: 104,
+                address: 5,
+                access: Access::Execute,
+            }
+        );
+    }
+
+    #[test]
+    fn no_execute_instruction_span() {
+        let mut c = Computer::from_string("104,7,99", IOMode::Buffer).unwrap();
+        c.mem.protection.protect(1..2, Protection::NoExecute);
+        assert_eq!(
+            c.run().unwrap_err(),
+            ComputerError::ProtectionFault {
+                ip: 0,
+                opcode: 104,
+                address: 1,
+                access: Access::Execute,
+            }
+        );
+    }
+
+    #[test]
+    fn no_execute_data_is_readable_and_writable() {
+        let mut c = Computer::from_string("1001,7,1,7,4,7,99,41", IOMode::Buffer).unwrap();
+        c.mem.protection.protect(7..8, Protection::NoExecute);
+        c.run().unwrap();
+        assert_eq!(c.output_from_buffer(), 42);
+    }
+
+    #[test]
+    fn checked_after_decode_is_cached() {
+        let mut c = Computer::from_string("1105,1,5,0,0,104,7,99", IOMode::Buffer).unwrap();
+        c.step().unwrap();
+        c.step().unwrap();
+        c.mem.instruction_pointer = 5;
+
+        c.mem.protection.protect(5..6, Protection::NoExecute);
+        assert!(c.run().is_err());
+        c.mem.protection.clear();
+        c.run().unwrap();
+        assert_eq!(c.buffer_io().output, [7, 7]);
+    }
+}
@@ -0,0 +1,113 @@
+mod snapshot {
+    use computer::{Computer, ComputerError, IOMode, Snapshot, StopReason};
+
+    // Keeps a running total of its inputs in relative memory far from the program,
+    // outputting it after each input and halting once the input is 0
+    const TOTAL: &str = "109,5000,3,100,1006,100,18,20201,0,100,0,204,0,1105,1,2,0,0,99";
+
+    fn paused() -> Computer {
+        let mut c = Computer::from_string(TOTAL, IOMode::Buffer).unwrap();
+        c.input_to_buffer(5);
+        c.input_to_buffer(7);
+        assert_eq!(c.run().unwrap(), StopReason::NeedsInput);
+        c.buffer_io().output.pop_front();
+        c
+    }
+
+    fn finish(c: &mut Computer) -> Vec<i64> {
+        c.input_to_buffer(30);
+        c.input_to_buffer(0);
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+        c.buffer_io().output.iter().copied().collect()
+    }
+
+    #[test]
+    fn json_round_trip_resumes_exactly() {
+        let mut c = paused();
+        let json = c.snapshot().to_json().unwrap();
+        let snapshot = Snapshot::from_json(&json).unwrap();
+        assert_eq!(snapshot, c.snapshot());
+
+        let mut resumed = Computer::from_snapshot(snapshot, IOMode::Buffer);
+        assert_eq!(resumed.mem.instruction_pointer, 2);
+        assert_eq!(resumed.mem.relative_base, 5000);
+        assert_eq!(resumed.instruction_count, c.instruction_count);
+        assert_eq!(finish(&mut resumed), [12, 42]);
+        assert_eq!(finish(&mut c), [12, 42]);
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let c = paused();
+        let bytes = c.snapshot().to_bytes().unwrap();
+        assert!(bytes.len() < c.snapshot().to_json().unwrap().len());
+
+        let mut resumed =
+            Computer::from_snapshot(Snapshot::from_bytes(&bytes).unwrap(), IOMode::Buffer);
+        assert_eq!(finish(&mut resumed), [12, 42]);
+    }
+
+    #[test]
+    fn restore_rewinds() {
+        let mut c = paused();
+        let snapshot = c.snapshot();
+        assert_eq!(finish(&mut c), [12, 42]);
+
+        c.restore(snapshot);
+        assert_eq!(c.mem.memory[5000], 12);
+        assert_eq!(finish(&mut c), [12, 42]);
+    }
+
+    #[test]
+    fn reset_returns_to_snapshot_image() {
+        let mut c = Computer::from_snapshot(paused().snapshot(), IOMode::Buffer);
+        finish(&mut c);
+        c.reset();
+        assert_eq!(c.mem.memory[5000], 12);
+        assert_eq!(c.mem.instruction_pointer, 0);
+    }
+
+    #[test]
+    fn pending_buffers_need_buffer_io() {
+        let snapshot = paused().snapshot();
+        assert!(snapshot.buffers.is_some());
+
+        let mut c = Computer::from_snapshot(snapshot, IOMode::Channel);
+        assert!(c.io_as::<computer::BufferIo>().is_none());
+        assert_eq!(c.mem.memory[5000], 12);
+    }
+
+    #[test]
+    fn invalid_snapshots() {
+        assert!(matches!(
+            Snapshot::<i64>::from_json("{}"),
+            Err(ComputerError::InvalidSnapshot { .. })
+        ));
+        assert!(matches!(
+            Snapshot::<i64>::from_bytes(&[1, 2, 3]),
+            Err(ComputerError::InvalidSnapshot { .. })
+        ));
+
+        let json = r#"{"memory":{"pages":{"0":[1,2,3]},"len":3},"instruction_pointer":0,"relative_base":0,"instruction_count":0,"buffers":null}"#;
+        assert_eq!(
+            Snapshot::<i64>::from_json(json).unwrap_err().to_string(),
+            "invalid snapshot: memory pages are malformed"
+        );
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn bigint_snapshot() {
+        use computer::BigInt;
+
+        let mut c = Computer::<BigInt>::load("3,0,99", IOMode::Buffer).unwrap();
+        let big: BigInt = "123456789012345678901234567890".parse().unwrap();
+        c.input_to_buffer(big.clone());
+        c.run().unwrap();
+
+        let snapshot = Snapshot::<BigInt>::from_json(&c.snapshot().to_json().unwrap()).unwrap();
+        assert_eq!(snapshot.memory.read(0), big);
+        let snapshot = Snapshot::<BigInt>::from_bytes(&c.snapshot().to_bytes().unwrap()).unwrap();
+        assert_eq!(snapshot.memory.read(0), big);
+    }
+}
@@ -0,0 +1,89 @@
+mod step {
+    use computer::{Computer, IOMode, MemoryWrite, Mode, OpCode, StopReason};
+
+    #[test]
+    fn step_add() {
+        let mut c = Computer::from_string("1001,4,3,4,5,99", IOMode::Buffer).unwrap();
+        let step = c.step().unwrap();
+        assert_eq!(step.ip, 0);
+        assert_eq!(step.operation.op_code, OpCode::Add);
+        assert_eq!(
+            step.operation.modes,
+            (Mode::Position, Mode::Immediate, Mode::Position)
+        );
+        assert_eq!(step.operands, [5, 3]);
+        assert_eq!(
+            step.writes,
+            [MemoryWrite {
+                address: 4,
+                old: 5,
+                new: 8
+            }]
+        );
+        assert_eq!(step.input, None);
+        assert_eq!(step.output, None);
+        assert_eq!(step.stop, None);
+        assert_eq!(c.mem.instruction_pointer, 4);
+    }
+
+    #[test]
+    fn step_io() {
+        let mut c = Computer::from_string("3,0,4,0,99", IOMode::Buffer).unwrap();
+        assert_eq!(c.step().unwrap().stop, Some(StopReason::NeedsInput));
+        assert_eq!(c.mem.instruction_pointer, 0);
+
+        c.input_to_buffer(42);
+        let step = c.step().unwrap();
+        assert_eq!(step.input, Some(42));
+        assert_eq!(step.writes[0].address, 0);
+
+        let step = c.step().unwrap();
+        assert_eq!(step.output, Some(42));
+        assert_eq!(step.operands, [42]);
+        assert_eq!(step.stop, None);
+
+        let step = c.step().unwrap();
+        assert_eq!(step.operation.op_code, OpCode::End);
+        assert_eq!(step.stop, Some(StopReason::Halted));
+    }
+
+    #[test]
+    fn step_output_stop_follows_break_on_output() {
+        let mut c = Computer::from_string("104,7,99", IOMode::Buffer).unwrap();
+        c.break_on_output = true;
+        assert_eq!(c.step().unwrap().stop, Some(StopReason::Output(7)));
+    }
+
+    #[test]
+    fn step_jump() {
+        let mut c = Computer::from_string("1105,0,4,1105,1,7,99,99", IOMode::Buffer).unwrap();
+        let step = c.step().unwrap();
+        assert_eq!(step.operands, [0]);
+        assert_eq!(c.mem.instruction_pointer, 3);
+
+        let step = c.step().unwrap();
+        assert_eq!(step.ip, 3);
+        assert_eq!(step.operands, [1, 7]);
+        assert_eq!(c.mem.instruction_pointer, 7);
+    }
+
+    #[test]
+    fn step_matches_run() {
+        let program = "3,9,8,9,10,9,4,9,99,-1,8";
+        let mut stepped = Computer::from_string(program, IOMode::Buffer).unwrap();
+        let mut ran = stepped.clone();
+        stepped.input_to_buffer(8);
+        ran.input_to_buffer(8);
+
+        ran.run().unwrap();
+        while stepped.step().unwrap().stop.is_none() {}
+        assert_eq!(stepped.output_from_buffer(), ran.output_from_buffer());
+        assert_eq!(stepped.mem.instruction_pointer, ran.mem.instruction_pointer);
+    }
+
+    #[test]
+    fn step_error() {
+        let mut c = Computer::from_string("11101,1,1,0,99", IOMode::Buffer).unwrap();
+        assert!(c.step().is_err());
+    }
+}
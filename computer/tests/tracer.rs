@@ -0,0 +1,157 @@
+mod tracer {
+    use computer::{
+        Computer, ComputerError, IOMode, MemoryWrite, Mode, OpCode, StopReason, TraceRecord, Tracer,
+    };
+    use std::io::{self, Write};
+    use std::sync::{Arc, Mutex};
+
+    // Reads a number, outputs it, then triples it in place
+    const ECHO_TRIPLE: &str = "3,0,4,0,1002,0,3,0,99";
+
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Shared {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+
+        fn records(&self) -> Vec<TraceRecord> {
+            self.text()
+                .lines()
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect()
+        }
+    }
+
+    fn traced(program: &str) -> (Computer, Shared) {
+        let out = Shared::default();
+        let mut c = Computer::from_string(program, IOMode::Buffer).unwrap();
+        c.tracer = Some(Tracer::new(out.clone()));
+        (c, out)
+    }
+
+    #[test]
+    fn one_line_per_instruction() {
+        let (mut c, out) = traced(ECHO_TRIPLE);
+        c.input_to_buffer(5);
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+
+        let records = out.records();
+        assert_eq!(records.len(), 4);
+        assert_eq!(
+            records[0],
+            TraceRecord {
+                step: 0,
+                ip: 0,
+                raw_opcode: 3,
+                opcode: OpCode::Input,
+                modes: (Mode::Position, Mode::Position, Mode::Position),
+                operands: vec![],
+                writes: vec![MemoryWrite {
+                    address: 0,
+                    old: 3,
+                    new: 5
+                }],
+                relative_base: 0,
+                input: Some(5),
+                defaulted: false,
+                output: None,
+            }
+        );
+        assert_eq!(records[1].output, Some(5));
+        assert_eq!(records[2].raw_opcode, 1002);
+        assert_eq!(records[2].modes.1, Mode::Immediate);
+        assert_eq!(records[2].operands, [5, 3]);
+        assert_eq!(records[2].writes[0].new, 15);
+        assert_eq!(records[3].opcode, OpCode::End);
+        assert_eq!(
+            records.iter().map(|r| r.step).collect::<Vec<_>>(),
+            [0, 1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn lines_are_json() {
+        let (mut c, out) = traced("109,5,99");
+        c.run().unwrap();
+        let first = out.text().lines().next().unwrap().to_string();
+        let value: serde_json::Value = serde_json::from_str(&first).unwrap();
+        assert_eq!(value["opcode"], "OffsetBase");
+        assert_eq!(value["raw_opcode"], 109);
+        assert_eq!(value["relative_base"], 0);
+    }
+
+    #[test]
+    fn waiting_for_input_is_not_recorded() {
+        let (mut c, out) = traced(ECHO_TRIPLE);
+        assert_eq!(c.run().unwrap(), StopReason::NeedsInput);
+        assert!(out.text().is_empty());
+
+        c.input_to_buffer(2);
+        c.run().unwrap();
+        assert_eq!(out.records()[0].input, Some(2));
+    }
+
+    #[test]
+    fn same_run_same_trace() {
+        let traces: Vec<String> = (0..2)
+            .map(|_| {
+                let (mut c, out) = traced(ECHO_TRIPLE);
+                c.input_to_buffer(7);
+                c.run().unwrap();
+                out.text()
+            })
+            .collect();
+        assert_eq!(traces[0], traces[1]);
+    }
+
+    #[test]
+    fn trace_to_file() {
+        let path = std::env::temp_dir().join(format!("trace-{}.jsonl", std::process::id()));
+        let mut c = Computer::from_string(ECHO_TRIPLE, IOMode::Buffer).unwrap();
+        c.tracer = Some(Tracer::create(&path).unwrap());
+        c.input_to_buffer(1);
+        c.run().unwrap();
+        c.tracer.take().unwrap().flush().unwrap();
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(text.lines().count(), 4);
+    }
+
+    #[test]
+    fn write_failure() {
+        struct Full;
+
+        impl Write for Full {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::other("disk full"))
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let mut c = Computer::from_string("104,1,99", IOMode::Buffer).unwrap();
+        c.tracer = Some(Tracer::new(Full));
+        assert_eq!(
+            c.run().unwrap_err(),
+            ComputerError::TraceFailed {
+                ip: 0,
+                reason: "disk full".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,158 @@
+mod transcript {
+    use computer::{
+        Computer, ComputerError, Event, History, IOMode, Replay, StopReason, Transcript,
+    };
+
+    // Outputs double each input until it reads 0
+    const DOUBLE_UNTIL_ZERO: &str = "3,15,1005,15,6,99,1002,15,2,16,4,16,1105,1,0,0,0";
+
+    fn record(inputs: &[i64]) -> Transcript {
+        let mut c = Computer::from_string(DOUBLE_UNTIL_ZERO, IOMode::Buffer).unwrap();
+        c.recorder = Some(Transcript::new());
+        for i in inputs {
+            c.input_to_buffer(*i);
+        }
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+        c.recorder.take().unwrap()
+    }
+
+    fn replay(program: &str, transcript: Transcript) -> Computer {
+        let mut c = Computer::from_string(program, IOMode::Buffer).unwrap();
+        c.replay = Some(Replay::new(transcript));
+        c
+    }
+
+    #[test]
+    fn records_instruction_counts() {
+        let transcript = record(&[3, 0]);
+        assert_eq!(
+            transcript.events,
+            [
+                Event::Input {
+                    instruction: 0,
+                    value: 3
+                },
+                Event::Output {
+                    instruction: 3,
+                    value: 6
+                },
+                Event::Input {
+                    instruction: 5,
+                    value: 0
+                },
+            ]
+        );
+        assert_eq!(transcript.inputs().copied().collect::<Vec<_>>(), [3, 0]);
+        assert_eq!(transcript.outputs().copied().collect::<Vec<_>>(), [6]);
+    }
+
+    #[test]
+    fn replay_matches() {
+        let mut c = replay(DOUBLE_UNTIL_ZERO, record(&[3, 5, 8, 0]));
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+        assert!(c.replay.as_ref().unwrap().is_finished());
+        assert_eq!(c.buffer_io().output, [6, 10, 16]);
+    }
+
+    #[test]
+    fn replay_detects_different_output() {
+        // Triples instead of doubling
+        let program = DOUBLE_UNTIL_ZERO.replace("1002,15,2", "1002,15,3");
+        let mut c = replay(&program, record(&[3, 0]));
+        let err = c.run().unwrap_err();
+        assert_eq!(
+            err,
+            ComputerError::ReplayMismatch {
+                ip: 10,
+                instruction: 3,
+                expected: Some(Event::Output {
+                    instruction: 3,
+                    value: 6
+                }),
+                output: Some(9),
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "replay diverged at ip 10, expected output 6 at instruction 3 but got output 9 at instruction 3"
+        );
+    }
+
+    #[test]
+    fn replay_detects_different_timing() {
+        // Takes an extra instruction before reading
+        let program = format!("1101,0,0,17,{}", DOUBLE_UNTIL_ZERO);
+        let mut c = replay(&program, record(&[3, 0]));
+        assert!(matches!(
+            c.run().unwrap_err(),
+            ComputerError::ReplayMismatch {
+                ip: 4,
+                instruction: 1,
+                output: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn replay_runs_out_of_inputs() {
+        let mut transcript = record(&[3, 0]);
+        transcript.events.pop();
+        let mut c = replay(DOUBLE_UNTIL_ZERO, transcript);
+        assert_eq!(c.run().unwrap(), StopReason::NeedsInput);
+        assert_eq!(c.replay.as_ref().unwrap().remaining(), 0);
+
+        c.replay = None;
+        c.input_to_buffer(0);
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+    }
+
+    #[test]
+    fn step_back_rewinds_replay() {
+        let mut c = replay(DOUBLE_UNTIL_ZERO, record(&[3, 0]));
+        c.history = Some(History::new(10));
+        for _ in 0..5 {
+            c.step().unwrap();
+        }
+        assert_eq!(c.replay.as_ref().unwrap().remaining(), 1);
+        assert!(c.rewind_to(0));
+        assert_eq!(c.replay.as_ref().unwrap().remaining(), 3);
+        assert!(c.buffer_io().input.is_empty());
+        assert!(c.buffer_io().output.is_empty());
+
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+        assert!(c.replay.as_ref().unwrap().is_finished());
+        assert_eq!(c.buffer_io().output, [6]);
+    }
+
+    #[test]
+    fn step_back_drops_recorded_events() {
+        let mut c = Computer::from_string(DOUBLE_UNTIL_ZERO, IOMode::Buffer).unwrap();
+        c.recorder = Some(Transcript::new());
+        c.history = Some(History::new(10));
+        c.input_to_buffer(3);
+        c.input_to_buffer(0);
+        for _ in 0..5 {
+            c.step().unwrap();
+        }
+        assert_eq!(c.recorder.as_ref().unwrap().events.len(), 2);
+        assert!(c.rewind_to(0));
+        assert_eq!(c.recorder.as_ref().unwrap().events, []);
+
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+        let transcript = c.recorder.take().unwrap();
+        assert_eq!(transcript, record(&[3, 0]));
+        let mut again = replay(DOUBLE_UNTIL_ZERO, transcript);
+        assert_eq!(again.run().unwrap(), StopReason::Halted);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let transcript = record(&[3, 5, 0]);
+        let path = std::env::temp_dir().join(format!("transcript-{}.json", std::process::id()));
+        transcript.save(&path).unwrap();
+        let loaded = Transcript::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, transcript);
+    }
+}
@@ -0,0 +1,133 @@
+mod word {
+    use computer::{Computer, ComputerError, IOMode, StopReason};
+
+    const BIG_SQUARE: &str = "1102,4000000000,4000000000,7,4,7,99,0";
+
+    #[test]
+    fn i64_day9_examples() {
+        let mut c = Computer::from_string("104,1125899906842624,99", IOMode::Buffer).unwrap();
+        c.run().unwrap();
+        assert_eq!(c.output_from_buffer(), 1125899906842624);
+
+        let mut c =
+            Computer::from_string("1102,34915192,34915192,7,4,7,99,0", IOMode::Buffer).unwrap();
+        c.run().unwrap();
+        assert_eq!(c.output_from_buffer(), 1219070632396864);
+    }
+
+    #[test]
+    fn i64_wraps_on_overflow() {
+        let mut c = Computer::from_string(BIG_SQUARE, IOMode::Buffer).unwrap();
+        c.run().unwrap();
+        assert_eq!(
+            c.output_from_buffer(),
+            4_000_000_000i64.wrapping_mul(4_000_000_000)
+        );
+    }
+
+    #[test]
+    fn checked_mul_overflow() {
+        let mut c = Computer::from_string(BIG_SQUARE, IOMode::Buffer).unwrap();
+        c.checked_arithmetic = true;
+        let err = c.run().unwrap_err();
+        assert_eq!(
+            err,
+            ComputerError::Overflow {
+                ip: 0,
+                opcode: 1102,
+                lhs: 4_000_000_000,
+                rhs: 4_000_000_000,
+                destination: 7,
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "overflow in opcode 1102 at ip 0 with operands 4000000000 and 4000000000 writing to address 7"
+        );
+        assert_eq!(c.mem.memory[7], 0);
+    }
+
+    #[test]
+    fn checked_add_overflow_relative_destination() {
+        let mut c =
+            Computer::from_string("109,10,21101,9223372036854775807,1,5,99", IOMode::Buffer)
+                .unwrap();
+        c.checked_arithmetic = true;
+        assert_eq!(
+            c.run().unwrap_err(),
+            ComputerError::Overflow {
+                ip: 2,
+                opcode: 21101,
+                lhs: i64::MAX,
+                rhs: 1,
+                destination: 15,
+            }
+        );
+    }
+
+    #[test]
+    fn checked_arithmetic_without_overflow() {
+        let mut c =
+            Computer::from_string("1102,34915192,34915192,7,4,7,99,0", IOMode::Buffer).unwrap();
+        c.checked_arithmetic = true;
+        c.run().unwrap();
+        assert_eq!(c.output_from_buffer(), 1219070632396864);
+    }
+
+    #[test]
+    fn i128_words() {
+        let mut c = Computer::<i128>::load(BIG_SQUARE, IOMode::Buffer).unwrap();
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+        assert_eq!(c.output_from_buffer(), 16_000_000_000_000_000_000);
+    }
+
+    #[test]
+    fn i128_literal_larger_than_i64() {
+        let mut c = Computer::<i128>::load(
+            "104,170141183460469231731687303715884105727,99",
+            IOMode::Buffer,
+        )
+        .unwrap();
+        c.run().unwrap();
+        assert_eq!(c.output_from_buffer(), i128::MAX);
+
+        let err = Computer::from_string(
+            "104,170141183460469231731687303715884105727,99",
+            IOMode::Buffer,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ComputerError::InvalidInteger { position: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn i128_address_out_of_range() {
+        let mut c = Computer::<i128>::load("4,100000000000000000000,99", IOMode::Buffer).unwrap();
+        assert_eq!(
+            c.run().unwrap_err(),
+            ComputerError::InvalidAddress {
+                ip: 0,
+                opcode: 4,
+                address: 100000000000000000000,
+            }
+        );
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn bigint_words() {
+        use computer::BigInt;
+
+        // Squares the input three times
+        let program = "3,100,2,100,100,100,2,100,100,100,2,100,100,100,4,100,99";
+        let mut c = Computer::<BigInt>::load(program, IOMode::Buffer).unwrap();
+        c.input_to_buffer(BigInt::from(1_000_000));
+        assert_eq!(c.run().unwrap(), StopReason::Halted);
+        let expected: BigInt = "1000000000000000000000000000000000000000000000000"
+            .parse()
+            .unwrap();
+        assert_eq!(c.output_from_buffer(), expected);
+    }
+}
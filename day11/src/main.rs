@@ -4,7 +4,7 @@ use std::fs::read_to_string;
 
 use num_enum::TryFromPrimitive;
 
-use computer::{Computer, IOMode};
+use computer::{Computer, IntcodeIo, IoError};
 
 #[derive(Hash, Eq, PartialEq, Debug, Clone, Copy)]
 struct Point {
@@ -39,15 +39,6 @@ enum TurnDirection {
     Clockwise = 1,
 }
 
-impl TurnDirection {
-    fn from_i64(input: i64) -> TurnDirection {
-        match TurnDirection::try_from(input) {
-            Ok(td) => td,
-            Err(e) => panic!("Cannot get turn direction from input: {}", e),
-        }
-    }
-}
-
 impl Direction {
     fn turn(&self, dir: TurnDirection) -> Direction {
         let i = *self as i64;
@@ -65,7 +56,7 @@ impl Direction {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Robot {
     facing: Direction,
     position: Point,
@@ -95,13 +86,6 @@ enum Colour {
 }
 
 impl Colour {
-    fn from_i64(input: i64) -> Colour {
-        match Colour::try_from(input) {
-            Ok(dir) => dir,
-            Err(e) => panic!("Cannot get colour from input: {}", e),
-        }
-    }
-
     fn to_char(&self) -> char {
         match self {
             Colour::Black => '█',
@@ -138,32 +122,55 @@ fn part2() {
         .for_each(|l| println!("{}", l));
 }
 
-fn run<'a>(initial: Colour) -> HashMap<Point, Colour> {
+#[derive(Debug, Clone)]
+struct Hull {
+    robot: Robot,
+    panel: HashMap<Point, Colour>,
+    paint: Option<Colour>,
+}
+
+impl IntcodeIo for Hull {
+    fn read(&mut self) -> Result<Option<i64>, IoError> {
+        let colour = *self
+            .panel
+            .entry(self.robot.position)
+            .or_insert(Colour::Black);
+        Ok(Some(colour as i64))
+    }
+
+    fn write(&mut self, value: i64) -> Result<(), IoError> {
+        match self.paint.take() {
+            None => self.paint = Some(Colour::try_from(value).map_err(invalid)?),
+            Some(colour_to_paint) => {
+                let turn = TurnDirection::try_from(value).map_err(invalid)?;
+                self.panel.insert(self.robot.position, colour_to_paint);
+                self.robot.turn(turn);
+                self.robot.step();
+            }
+        }
+        Ok(())
+    }
+}
+
+fn invalid<E: std::fmt::Display>(e: E) -> IoError {
+    IoError::InvalidInput(e.to_string())
+}
+
+fn run(initial: Colour) -> HashMap<Point, Colour> {
     let input = read_to_string("input").expect("failed to read input file");
-    let mut c = &mut Computer::from_string(&input[..], IOMode::Buffer);
-    c.enable_logger = false;
-    c.break_on_output = true;
-    let robot = &mut Robot::new();
+    let robot = Robot::new();
     let mut panel: HashMap<Point, Colour> = HashMap::new();
 
     panel.insert(robot.position, initial);
 
-    while step(c, robot, &mut panel) {}
+    let hull = Hull {
+        robot,
+        panel,
+        paint: None,
+    };
+    let mut c = Computer::with_io(&input[..], hull).expect("failed to parse program");
+    c.run().expect("computer failed");
 
-    panel
-}
-
-fn step(c: &mut Computer, robot: &mut Robot, panel: &mut HashMap<Point, Colour>) -> bool {
-    c.input_to_buffer(*panel.entry(robot.position).or_insert(Colour::Black) as i64);
-    c.run();
-    let is_running = c.is_running();
-    if is_running {
-        let colour_to_paint = Colour::from_i64(c.output_from_buffer());
-        c.run();
-        let direction_to_turn = TurnDirection::from_i64(c.output_from_buffer());
-        panel.insert(robot.position, colour_to_paint);
-        robot.turn(direction_to_turn);
-        robot.step();
-    }
-    return is_running;
+    let hull = c.io_as::<Hull>().expect("computer lost its hull");
+    std::mem::take(&mut hull.panel)
 }
@@ -1,4 +1,4 @@
-use computer::{Channel, Computer, IOMode};
+use computer::{Computer, ComputerHandle, IOMode};
 use crossbeam_channel::{unbounded, Receiver};
 
 use std::cmp::Ordering;
@@ -75,7 +75,7 @@ impl Tile {
 
 struct MyGame {
     tiles: Vec<Tile>,
-    input_chan: Channel,
+    computer: ComputerHandle,
     tile_reciever: Receiver<Tile>,
     score: i64,
 }
@@ -83,28 +83,25 @@ struct MyGame {
 impl MyGame {
     pub fn new(_ctx: &mut Context) -> MyGame {
         let input = read_to_string("input").expect("failed to read input file");
-        let mut c = Computer::from_string(&input, IOMode::Channel);
+        let mut c = Computer::from_string(&input, IOMode::Buffer).expect("failed to parse program");
         c.mem.memory[0] = 2;
-        c.enable_logger = false;
-
-        let output_chan = c.output_channel.clone();
-        let input_chan = c.input_channel.clone();
+        let computer = c.spawn();
+        let output = computer.receiver().clone();
         let (tile_sender, tile_reciever) = unbounded();
 
+        // Stops once the computer halts and its outputs are drained
         thread::spawn(move || {
-            &c.run();
-        });
-        thread::spawn(move || loop {
-            let mut new_tiles = Vec::new();
-            update_from_output(&output_chan, &mut new_tiles);
-            for t in new_tiles {
-                tile_sender.send(t).unwrap();
+            let mut values = output.iter();
+            while let Some(t) = next_tile(&mut values) {
+                if tile_sender.send(t).is_err() {
+                    break;
+                }
             }
         });
 
         let g = MyGame {
             tile_reciever,
-            input_chan,
+            computer,
             tiles: Vec::new(),
             score: 0,
         };
@@ -138,19 +135,18 @@ impl MyGame {
         let ball = MyGame::get_tile_by_type(&self.tiles, TileType::Ball)?;
 
         let x_diff = ball.position.x - paddle.position.x;
-        match x_diff.cmp(&0) {
-            Ordering::Greater => self.input_chan.send(1),
-            Ordering::Equal => self.input_chan.send(0),
-            Ordering::Less => self.input_chan.send(-1),
-        }
+        let direction = match x_diff.cmp(&0) {
+            Ordering::Greater => 1,
+            Ordering::Equal => 0,
+            Ordering::Less => -1,
+        };
 
-        Some(())
+        self.computer.send(direction).ok()
     }
 
     fn update_from_computer(&mut self) {
         //now read it all
-        while !self.tile_reciever.is_empty() {
-            let new_tile = self.tile_reciever.recv().unwrap();
+        while let Ok(new_tile) = self.tile_reciever.try_recv() {
             if new_tile.tile_type == TileType::Score {
                 self.score = new_tile.score;
                 println!("Score Updated: {}", self.score);
@@ -169,7 +165,7 @@ impl ggez::event::EventHandler for MyGame {
         self.update_from_computer();
         self.follow_ball();
         //wait for update
-        while self.tile_reciever.is_empty() {}
+        while self.tile_reciever.is_empty() && !self.computer.is_finished() {}
 
         Ok(())
     }
@@ -239,12 +235,13 @@ fn part2() {
 }
 
 fn part1(input: &str) {
-    let mut c = Computer::from_string(&input, IOMode::Channel);
+    let c = Computer::from_string(&input, IOMode::Buffer).expect("failed to parse program");
+    let finished = c.spawn().join().expect("computer failed");
 
+    let mut values = finished.outputs.into_iter();
     let mut tiles = Vec::new();
-    c.run();
-    while !c.output_channel.receiver.is_empty() {
-        update_from_output(&c.output_channel, &mut tiles);
+    while let Some(t) = next_tile(&mut values) {
+        tiles.push(t);
     }
 
     println!(
@@ -256,22 +253,23 @@ fn part1(input: &str) {
     );
 }
 
-fn update_from_output(c: &Channel, tiles: &mut Vec<Tile>) {
+fn next_tile(values: &mut impl Iterator<Item = i64>) -> Option<Tile> {
     let position = Point {
-        x: c.recv(),
-        y: c.recv(),
+        x: values.next()?,
+        y: values.next()?,
     };
+    let value = values.next()?;
 
-    tiles.push(match position.x == -1 && position.y == 0 {
+    Some(match position.x == -1 && position.y == 0 {
         true => Tile {
             position,
             tile_type: TileType::Score,
-            score: c.recv(),
+            score: value,
         },
         false => Tile {
             position,
             score: 0,
-            tile_type: TileType::from_i64(c.recv()),
+            tile_type: TileType::from_i64(value),
         },
-    });
+    })
 }
@@ -1,28 +1,31 @@
-#[macro_use]
-extern crate lazy_static;
-
 use computer::{Computer, IOMode};
 use permutohedron::Heap;
 use std::fs::read_to_string;
 
 fn main() {
+    let input = read_to_string("input").expect("failed to read input file");
+    let program =
+        Computer::from_string(&input[..], IOMode::Buffer).expect("failed to parse program");
+
     let max = Heap::new(&mut vec![0, 1, 2, 3, 4])
-        .map(run)
+        .map(|inputs| run(&program, inputs))
         .max()
         .expect("something is broken, enjoy debugging");
 
     println!("Solution Part 1 : {:?}", max);
 
     let max = Heap::new(&mut vec![5, 6, 7, 8, 9])
-        .map(run)
+        .map(|inputs| run(&program, inputs))
         .max()
         .expect("something is broken, enjoy debugging");
 
     println!("Solution Part 2: {:?}", max);
 }
 
-fn run(inputs: Vec<i32>) -> i32 {
-    let mut amps: Vec<Computer> = (0..5).map(|i| create_computer(i.to_string())).collect();
+fn run(program: &Computer, inputs: Vec<i64>) -> i64 {
+    let mut amps: Vec<Computer> = (0..5)
+        .map(|i| create_computer(program, i.to_string()))
+        .collect();
     for i in 0..5 {
         amps[i].input_to_buffer(inputs[i]);
     }
@@ -30,11 +33,11 @@ fn run(inputs: Vec<i32>) -> i32 {
     amps[0].input_to_buffer(0);
 
     loop {
-        amps[0].run();
+        amps[0].run().expect("computer failed");
         for i in 1..5 {
             let res = amps[i - 1].output_from_buffer();
             amps[i].input_to_buffer(res);
-            amps[i].run();
+            amps[i].run().expect("computer failed");
         }
 
         let output = amps[4].output_from_buffer();
@@ -46,13 +49,9 @@ fn run(inputs: Vec<i32>) -> i32 {
     }
 }
 
-fn create_computer(name: String) -> computer::Computer {
-    lazy_static! {
-        static ref INPUT: String = read_to_string("input").expect("failed to read input file");
-    }
-    let mut c = Computer::from_string(&INPUT[..], IOMode::Buffer);
-    c.log_prefix = format!("Amp {}", name);
-    c.enable_logger = false;
+fn create_computer(program: &Computer, name: String) -> computer::Computer {
+    let mut c = program.fork();
+    c.name = format!("Amp {}", name);
     c.break_on_output = true;
     c
 }